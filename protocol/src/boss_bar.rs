@@ -0,0 +1,112 @@
+use std::io::{Read, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{chat::Message, FieldReadError, FieldWriteError, PacketField, VarInt};
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum BossBarColor: VarInt {
+        Pink = VarInt(0),
+        Blue = VarInt(1),
+        Red = VarInt(2),
+        Green = VarInt(3),
+        Yellow = VarInt(4),
+        Purple = VarInt(5),
+        White = VarInt(6),
+    }
+}
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
+    pub enum BossBarDivision: VarInt {
+        Progress = VarInt(0),
+        Notched6 = VarInt(1),
+        Notched10 = VarInt(2),
+        Notched12 = VarInt(3),
+        Notched20 = VarInt(4),
+    }
+}
+
+#[derive(Debug)]
+pub enum BossBarAction {
+    Add {
+        title: Message,
+        health: f32,
+        color: BossBarColor,
+        division: BossBarDivision,
+        flags: u8,
+    },
+    Remove,
+    UpdateHealth(f32),
+    UpdateTitle(Message),
+    UpdateStyle {
+        color: BossBarColor,
+        division: BossBarDivision,
+    },
+    UpdateFlags(u8),
+}
+
+impl PacketField for BossBarAction {
+    fn read_from(buffer: &mut dyn Read) -> Result<BossBarAction, FieldReadError> {
+        match VarInt::read_from(buffer)? {
+            VarInt(0) => Ok(BossBarAction::Add {
+                title: Message::read_from(buffer)?,
+                health: f32::read_from(buffer)?,
+                color: BossBarColor::read_from(buffer)?,
+                division: BossBarDivision::read_from(buffer)?,
+                flags: u8::read_from(buffer)?,
+            }),
+            VarInt(1) => Ok(BossBarAction::Remove),
+            VarInt(2) => Ok(BossBarAction::UpdateHealth(f32::read_from(buffer)?)),
+            VarInt(3) => Ok(BossBarAction::UpdateTitle(Message::read_from(buffer)?)),
+            VarInt(4) => Ok(BossBarAction::UpdateStyle {
+                color: BossBarColor::read_from(buffer)?,
+                division: BossBarDivision::read_from(buffer)?,
+            }),
+            VarInt(5) => Ok(BossBarAction::UpdateFlags(u8::read_from(buffer)?)),
+            id => Err(FieldReadError::InvalidEnumId(format!("{:?}", id))),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            BossBarAction::Add {
+                title,
+                health,
+                color,
+                division,
+                flags,
+            } => {
+                VarInt(0).write_to(buffer)?;
+                title.write_to(buffer)?;
+                health.write_to(buffer)?;
+                color.write_to(buffer)?;
+                division.write_to(buffer)?;
+                flags.write_to(buffer)?;
+            }
+            BossBarAction::Remove => VarInt(1).write_to(buffer)?,
+            BossBarAction::UpdateHealth(health) => {
+                VarInt(2).write_to(buffer)?;
+                health.write_to(buffer)?;
+            }
+            BossBarAction::UpdateTitle(title) => {
+                VarInt(3).write_to(buffer)?;
+                title.write_to(buffer)?;
+            }
+            BossBarAction::UpdateStyle { color, division } => {
+                VarInt(4).write_to(buffer)?;
+                color.write_to(buffer)?;
+                division.write_to(buffer)?;
+            }
+            BossBarAction::UpdateFlags(flags) => {
+                VarInt(5).write_to(buffer)?;
+                flags.write_to(buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -27,3 +27,12 @@ impl PacketField for Message {
         serde_json::to_string(self)?.write_to(buffer)
     }
 }
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy)]
+    pub enum ChatPosition: u8 {
+        Chat = 0,
+        System = 1,
+        GameInfo = 2,
+    }
+}
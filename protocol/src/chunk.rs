@@ -0,0 +1,287 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use crate::{
+    io::VarIntPrefixedVec, nbt::Nbt, FieldReadError, FieldWriteError, PacketField, VarInt,
+};
+
+pub const SECTION_WIDTH: usize = 16;
+pub const SECTION_VOLUME: usize = SECTION_WIDTH * SECTION_WIDTH * SECTION_WIDTH;
+
+/// The amount of bits needed to index the global block state palette in 1.18.1.
+const GLOBAL_PALETTE_BITS: u8 = 15;
+const BIOMES_PER_SECTION: usize = 64;
+const LIGHT_ARRAY_SIZE: usize = 2048;
+
+/// A 16x16x16 section of a chunk, storing global block state IDs in `y, z, x` order.
+#[derive(Debug, Clone)]
+pub struct ChunkSection {
+    blocks: Vec<u16>,
+    pub biome: VarInt,
+}
+
+impl ChunkSection {
+    pub fn new(biome: VarInt) -> ChunkSection {
+        ChunkSection {
+            blocks: vec![0; SECTION_VOLUME],
+            biome,
+        }
+    }
+
+    fn index(x: usize, y: usize, z: usize) -> usize {
+        (y << 8) | (z << 4) | x
+    }
+
+    pub fn get(&self, x: usize, y: usize, z: usize) -> u16 {
+        self.blocks[ChunkSection::index(x, y, z)]
+    }
+
+    pub fn set(&mut self, x: usize, y: usize, z: usize, state: u16) {
+        self.blocks[ChunkSection::index(x, y, z)] = state;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocks.iter().all(|&state| state == 0)
+    }
+
+    fn block_count(&self) -> i16 {
+        self.blocks.iter().filter(|&&state| state != 0).count() as i16
+    }
+}
+
+impl PacketField for ChunkSection {
+    fn read_from(buffer: &mut dyn Read) -> Result<ChunkSection, FieldReadError> {
+        let _block_count = i16::read_from(buffer)?;
+        let blocks = read_paletted(buffer, SECTION_VOLUME)?;
+        let biomes = read_paletted(buffer, BIOMES_PER_SECTION)?;
+
+        Ok(ChunkSection {
+            blocks: blocks.into_iter().map(|state| state as u16).collect(),
+            biome: VarInt(biomes[0] as i32),
+        })
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        self.block_count().write_to(buffer)?;
+
+        let mut palette = Vec::new();
+        let mut palette_indices = HashMap::new();
+        for &state in &self.blocks {
+            palette_indices.entry(state).or_insert_with(|| {
+                palette.push(state);
+                palette.len() - 1
+            });
+        }
+
+        if palette.len() == 1 {
+            0u8.write_to(buffer)?;
+            VarInt(palette[0] as i32).write_to(buffer)?;
+            VarInt(0).write_to(buffer)?;
+        } else {
+            let bits = bits_needed(palette.len()).max(4);
+            if bits <= 8 {
+                bits.write_to(buffer)?;
+                VarIntPrefixedVec(palette.iter().map(|&s| VarInt(s as i32)).collect())
+                    .write_to(buffer)?;
+
+                let indices = self
+                    .blocks
+                    .iter()
+                    .map(|state| palette_indices[state] as u64)
+                    .collect::<Vec<_>>();
+                VarIntPrefixedVec(pack(&indices, bits)).write_to(buffer)?;
+            } else {
+                GLOBAL_PALETTE_BITS.write_to(buffer)?;
+                let states = self.blocks.iter().map(|&s| s as u64).collect::<Vec<_>>();
+                VarIntPrefixedVec(pack(&states, GLOBAL_PALETTE_BITS)).write_to(buffer)?;
+            }
+        }
+
+        // Biomes are the same throughout a section, so they always use a single value palette.
+        0u8.write_to(buffer)?;
+        self.biome.write_to(buffer)?;
+        VarInt(0).write_to(buffer)
+    }
+}
+
+fn read_paletted(buffer: &mut dyn Read, count: usize) -> Result<Vec<u64>, FieldReadError> {
+    let bits = u8::read_from(buffer)?;
+    if bits == 0 {
+        let value = VarInt::read_from(buffer)?.0 as u64;
+        let _ = VarInt::read_from(buffer)?;
+        return Ok(vec![value; count]);
+    }
+
+    let palette = if count == SECTION_VOLUME && bits > 8 {
+        None
+    } else {
+        Some(VarIntPrefixedVec::<VarInt>::read_from(buffer)?.0)
+    };
+
+    let data = VarIntPrefixedVec::<u64>::read_from(buffer)?.0;
+    let values = unpack(&data, bits, count);
+
+    Ok(match palette {
+        Some(palette) => values
+            .into_iter()
+            .map(|i| palette.get(i as usize).map(|v| v.0 as u64).unwrap_or(0))
+            .collect(),
+        None => values,
+    })
+}
+
+/// Returns the amount of bits required to represent `count` distinct values.
+pub fn bits_needed(count: usize) -> u8 {
+    (usize::BITS - count.saturating_sub(1).leading_zeros()) as u8
+}
+
+/// Packs values into longs the way 1.16+ does, without entries spanning multiple longs.
+pub fn pack(values: &[u64], bits: u8) -> Vec<u64> {
+    let per_long = 64 / bits as usize;
+    let mask = (1u64 << bits) - 1;
+
+    values
+        .chunks(per_long)
+        .map(|chunk| {
+            chunk.iter().enumerate().fold(0, |long, (i, value)| {
+                long | ((value & mask) << (i * bits as usize))
+            })
+        })
+        .collect()
+}
+
+/// The inverse of [`pack`], reading `count` values of `bits` bits each.
+pub fn unpack(longs: &[u64], bits: u8, count: usize) -> Vec<u64> {
+    if bits == 0 {
+        return vec![0; count];
+    }
+
+    let per_long = 64 / bits as usize;
+    let mask = (1u64 << bits) - 1;
+
+    (0..count)
+        .map(|i| {
+            longs
+                .get(i / per_long)
+                .map(|long| (long >> ((i % per_long) * bits as usize)) & mask)
+                .unwrap_or(0)
+        })
+        .collect()
+}
+
+packet_field! {
+    #[derive(Debug, Clone)]
+    pub struct BlockEntity {
+        pub packed_xz: u8,
+        pub y: i16,
+        pub kind: VarInt,
+        pub data: Nbt,
+    }
+}
+
+packet_field! {
+    #[derive(Debug, Clone)]
+    pub struct LightArray {
+        pub data: VarIntPrefixedVec<u8>,
+    }
+}
+
+packet_field! {
+    #[derive(Debug, Clone)]
+    pub struct LightData {
+        pub trust_edges: bool,
+        pub sky_light_mask: VarIntPrefixedVec<u64>,
+        pub block_light_mask: VarIntPrefixedVec<u64>,
+        pub empty_sky_light_mask: VarIntPrefixedVec<u64>,
+        pub empty_block_light_mask: VarIntPrefixedVec<u64>,
+        pub sky_light: VarIntPrefixedVec<LightArray>,
+        pub block_light: VarIntPrefixedVec<LightArray>,
+    }
+}
+
+impl LightData {
+    /// Full sky light and no block light for every section, including the one below and the one
+    /// above the world, so that a static map is always visible.
+    pub fn full_bright(section_count: usize) -> LightData {
+        let light_sections = section_count + 2;
+        let mask = VarIntPrefixedVec(pack(&vec![1; light_sections], 1));
+
+        LightData {
+            trust_edges: true,
+            sky_light_mask: mask.clone(),
+            block_light_mask: VarIntPrefixedVec(Vec::new()),
+            empty_sky_light_mask: VarIntPrefixedVec(Vec::new()),
+            empty_block_light_mask: mask,
+            sky_light: VarIntPrefixedVec(vec![
+                LightArray {
+                    data: VarIntPrefixedVec(vec![0xff; LIGHT_ARRAY_SIZE]),
+                };
+                light_sections
+            ]),
+            block_light: VarIntPrefixedVec(Vec::new()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_round_trip() {
+        for bits in [1, 4, 5, 8, 15] {
+            let values = (0..SECTION_VOLUME as u64)
+                .map(|i| (i * 31) % (1 << bits))
+                .collect::<Vec<_>>();
+
+            let packed = pack(&values, bits);
+            assert_eq!(packed.len(), values.len().div_ceil(64 / bits as usize));
+            assert_eq!(unpack(&packed, bits, values.len()), values);
+        }
+    }
+
+    #[test]
+    fn bits_needed_for_palettes() {
+        assert_eq!(bits_needed(1), 0);
+        assert_eq!(bits_needed(2), 1);
+        assert_eq!(bits_needed(16), 4);
+        assert_eq!(bits_needed(17), 5);
+        assert_eq!(bits_needed(256), 8);
+        assert_eq!(bits_needed(257), 9);
+    }
+
+    fn section_round_trip(section: &ChunkSection) {
+        let buffer = section.write_to_vec().unwrap();
+        let read = ChunkSection::read_from(&mut &buffer[..]).unwrap();
+
+        assert_eq!(read.biome.0, section.biome.0);
+        assert_eq!(read.blocks, section.blocks);
+    }
+
+    #[test]
+    fn single_state_section_round_trip() {
+        let mut section = ChunkSection::new(VarInt(3));
+        section.blocks.fill(1);
+        section_round_trip(&section);
+    }
+
+    #[test]
+    fn indirect_palette_section_round_trip() {
+        let mut section = ChunkSection::new(VarInt(1));
+        for x in 0..SECTION_WIDTH {
+            section.set(x, x, 15 - x, 10 + x as u16 * 7);
+        }
+        section_round_trip(&section);
+    }
+
+    #[test]
+    fn direct_palette_section_round_trip() {
+        let mut section = ChunkSection::new(VarInt(0));
+        for (i, state) in section.blocks.iter_mut().enumerate() {
+            *state = (i % 300) as u16 * 50;
+        }
+        section_round_trip(&section);
+    }
+}
@@ -0,0 +1,273 @@
+use std::io::{Read, Write};
+
+use crate::{
+    chat::Message,
+    io::{BooleanPrefixedOption, VarIntPrefixedVec},
+    FieldReadError, FieldWriteError, PacketField, VarInt,
+};
+
+const NODE_ROOT: u8 = 0;
+const NODE_LITERAL: u8 = 1;
+const NODE_ARGUMENT: u8 = 2;
+
+const FLAG_EXECUTABLE: u8 = 0x04;
+const FLAG_REDIRECT: u8 = 0x08;
+const FLAG_SUGGESTIONS: u8 = 0x10;
+
+/// Lets the client ask the server for suggestions through a tab-complete request.
+pub const ASK_SERVER: &str = "minecraft:ask_server";
+
+/// An argument parser, of which only the ones built into Brigadier are supported.
+#[derive(Debug, Clone)]
+pub enum Parser {
+    Bool,
+    Integer { min: Option<i32>, max: Option<i32> },
+    String(StringKind),
+}
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy)]
+    pub enum StringKind: VarInt {
+        SingleWord = VarInt(0),
+        QuotablePhrase = VarInt(1),
+        GreedyPhrase = VarInt(2),
+    }
+}
+
+impl PacketField for Parser {
+    fn read_from(buffer: &mut dyn Read) -> Result<Parser, FieldReadError> {
+        match String::read_from(buffer)?.as_str() {
+            "brigadier:bool" => Ok(Parser::Bool),
+            "brigadier:integer" => {
+                let flags = u8::read_from(buffer)?;
+                let min = if flags & 0x01 != 0 {
+                    Some(i32::read_from(buffer)?)
+                } else {
+                    None
+                };
+                let max = if flags & 0x02 != 0 {
+                    Some(i32::read_from(buffer)?)
+                } else {
+                    None
+                };
+
+                Ok(Parser::Integer { min, max })
+            }
+            "brigadier:string" => Ok(Parser::String(StringKind::read_from(buffer)?)),
+            parser => Err(FieldReadError::InvalidEnumId(parser.to_string())),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            Parser::Bool => "brigadier:bool".to_string().write_to(buffer)?,
+            Parser::Integer { min, max } => {
+                "brigadier:integer".to_string().write_to(buffer)?;
+
+                let flags = min.map_or(0, |_| 0x01) | max.map_or(0, |_| 0x02);
+                flags.write_to(buffer)?;
+                for bound in [min, max].into_iter().flatten() {
+                    bound.write_to(buffer)?;
+                }
+            }
+            Parser::String(kind) => {
+                "brigadier:string".to_string().write_to(buffer)?;
+                kind.write_to(buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum NodeKind {
+    Root,
+    Literal { name: String },
+    Argument { name: String, parser: Parser },
+}
+
+/// A node in the flattened command graph, with children referring to other nodes by index.
+#[derive(Debug, Clone)]
+pub struct CommandNode {
+    pub kind: NodeKind,
+    pub executable: bool,
+    pub children: VarIntPrefixedVec<VarInt>,
+    pub redirect: Option<VarInt>,
+    pub suggestions: Option<String>,
+}
+
+impl PacketField for CommandNode {
+    fn read_from(buffer: &mut dyn Read) -> Result<CommandNode, FieldReadError> {
+        let flags = u8::read_from(buffer)?;
+        let children = VarIntPrefixedVec::read_from(buffer)?;
+        let redirect = if flags & FLAG_REDIRECT != 0 {
+            Some(VarInt::read_from(buffer)?)
+        } else {
+            None
+        };
+
+        let kind = match flags & 0x03 {
+            NODE_ROOT => NodeKind::Root,
+            NODE_LITERAL => NodeKind::Literal {
+                name: String::read_from(buffer)?,
+            },
+            NODE_ARGUMENT => NodeKind::Argument {
+                name: String::read_from(buffer)?,
+                parser: Parser::read_from(buffer)?,
+            },
+            kind => return Err(FieldReadError::InvalidEnumId(format!("{:?}", kind))),
+        };
+
+        let suggestions = if flags & FLAG_SUGGESTIONS != 0 {
+            Some(String::read_from(buffer)?)
+        } else {
+            None
+        };
+
+        Ok(CommandNode {
+            kind,
+            executable: flags & FLAG_EXECUTABLE != 0,
+            children,
+            redirect,
+            suggestions,
+        })
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        let mut flags = match self.kind {
+            NodeKind::Root => NODE_ROOT,
+            NodeKind::Literal { .. } => NODE_LITERAL,
+            NodeKind::Argument { .. } => NODE_ARGUMENT,
+        };
+        if self.executable {
+            flags |= FLAG_EXECUTABLE;
+        }
+        if self.redirect.is_some() {
+            flags |= FLAG_REDIRECT;
+        }
+        if self.suggestions.is_some() {
+            flags |= FLAG_SUGGESTIONS;
+        }
+
+        flags.write_to(buffer)?;
+        self.children.write_to(buffer)?;
+        if let Some(redirect) = self.redirect {
+            redirect.write_to(buffer)?;
+        }
+
+        match &self.kind {
+            NodeKind::Root => {}
+            NodeKind::Literal { name } => name.write_to(buffer)?,
+            NodeKind::Argument { name, parser } => {
+                name.write_to(buffer)?;
+                parser.write_to(buffer)?;
+            }
+        }
+
+        if let Some(ref suggestions) = self.suggestions {
+            suggestions.write_to(buffer)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A command as a tree of literals and arguments, which is easier to build than the graph.
+#[derive(Debug, Clone)]
+pub struct Command {
+    name: String,
+    parser: Option<Parser>,
+    executable: bool,
+    suggestions: Option<String>,
+    children: Vec<Command>,
+}
+
+impl Command {
+    pub fn literal<S: Into<String>>(name: S) -> Command {
+        Command {
+            name: name.into(),
+            parser: None,
+            executable: false,
+            suggestions: None,
+            children: Vec::new(),
+        }
+    }
+
+    pub fn argument<S: Into<String>>(name: S, parser: Parser) -> Command {
+        Command {
+            parser: Some(parser),
+            ..Command::literal(name)
+        }
+    }
+
+    pub fn executes(mut self) -> Command {
+        self.executable = true;
+        self
+    }
+
+    pub fn suggests<S: Into<String>>(mut self, suggestions: S) -> Command {
+        self.suggestions = Some(suggestions.into());
+        self
+    }
+
+    pub fn then(mut self, child: Command) -> Command {
+        self.children.push(child);
+        self
+    }
+}
+
+/// Flattens commands into the node graph sent to the client, returning the nodes along with the
+/// index of the root node.
+pub fn graph(commands: &[Command]) -> (Vec<CommandNode>, VarInt) {
+    fn add(command: &Command, nodes: &mut Vec<CommandNode>) -> VarInt {
+        let children = command
+            .children
+            .iter()
+            .map(|child| add(child, nodes))
+            .collect();
+
+        nodes.push(CommandNode {
+            kind: match command.parser {
+                Some(ref parser) => NodeKind::Argument {
+                    name: command.name.clone(),
+                    parser: parser.clone(),
+                },
+                None => NodeKind::Literal {
+                    name: command.name.clone(),
+                },
+            },
+            executable: command.executable,
+            children: VarIntPrefixedVec(children),
+            redirect: None,
+            suggestions: command.suggestions.clone(),
+        });
+
+        VarInt(nodes.len() as i32 - 1)
+    }
+
+    let mut nodes = Vec::new();
+    let children = commands
+        .iter()
+        .map(|command| add(command, &mut nodes))
+        .collect();
+
+    nodes.push(CommandNode {
+        kind: NodeKind::Root,
+        executable: false,
+        children: VarIntPrefixedVec(children),
+        redirect: None,
+        suggestions: None,
+    });
+
+    let root = VarInt(nodes.len() as i32 - 1);
+    (nodes, root)
+}
+
+packet_field! {
+    #[derive(Debug)]
+    pub struct TabCompleteMatch {
+        pub text: String,
+        pub tooltip: BooleanPrefixedOption<Message>,
+    }
+}
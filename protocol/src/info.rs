@@ -4,6 +4,7 @@ use std::{
 };
 
 use serde::{Deserialize, Serialize};
+use uuid::Uuid;
 
 use crate::{chat::Message, FieldReadError, FieldWriteError, PacketField};
 
@@ -36,6 +37,10 @@ impl ServerInfo {
             favicon: favicon.map(|data| format!("data:image/png;base64,{}", base64::encode(data))),
         }
     }
+
+    pub fn set_players(&mut self, players: Option<ServerPlayerInfo>) {
+        self.players = players;
+    }
 }
 
 impl PacketField for ServerInfo {
@@ -52,15 +57,34 @@ impl PacketField for ServerInfo {
 pub struct ServerPlayerInfo {
     max: isize,
     online: isize,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sample: Vec<PlayerSample>,
 }
 
 impl ServerPlayerInfo {
     pub fn simple(online: isize, max: isize) -> ServerPlayerInfo {
-        ServerPlayerInfo { online, max }
+        ServerPlayerInfo {
+            online,
+            max,
+            sample: Vec::new(),
+        }
+    }
+
+    /// Adds players shown when hovering over the player count, which can be any text.
+    pub fn with_sample(mut self, sample: Vec<PlayerSample>) -> ServerPlayerInfo {
+        self.sample = sample;
+        self
     }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerSample {
+    pub name: String,
+    pub id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VersionInfo {
     pub name: Cow<'static, str>,
     pub protocol: usize,
@@ -24,6 +24,26 @@ impl PacketField for String {
     }
 }
 
+/// A string prefixed by its length as an unsigned short, as written by Java's `writeUTF`. This
+/// is used by proxy plugin messages rather than the protocol itself.
+#[derive(Debug, Clone)]
+pub struct ShortPrefixedString(pub String);
+
+impl PacketField for ShortPrefixedString {
+    fn read_from(buffer: &mut dyn Read) -> Result<Self, FieldReadError> {
+        let length = u16::read_from(buffer)?;
+        let mut string_buffer = vec![0; length as usize];
+        buffer.read_exact(&mut string_buffer)?;
+        Ok(ShortPrefixedString(String::from_utf8(string_buffer)?))
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        (self.0.len() as u16).write_to(buffer)?;
+        buffer.write_all(self.0.as_bytes())?;
+        Ok(())
+    }
+}
+
 macro_rules! impl_int {
     ($($typ:ident),+) => {
         $(
@@ -90,7 +110,7 @@ impl PacketField for bool {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct VarIntPrefixedVec<T>(pub Vec<T>);
 
 impl<T: PacketField> PacketField for VarIntPrefixedVec<T> {
@@ -141,7 +161,7 @@ impl PacketField for RawBytes {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct BooleanPrefixedOption<T>(pub Option<T>);
 
 impl<T: PacketField> PacketField for BooleanPrefixedOption<T> {
@@ -46,6 +46,12 @@ macro_rules! packet {
                 }
             }
 
+            fn name(&self) -> &'static str {
+                match self {
+                    $(Self::$packet { .. } => stringify!($packet),)*
+                }
+            }
+
             fn write_to(&self, buffer: &mut dyn std::io::Write) -> Result<(), crate::WriteError> {
                 use crate::PacketField;
 
@@ -164,12 +170,17 @@ macro_rules! packet_field {
     };
 }
 
+pub mod boss_bar;
 pub mod chat;
+pub mod chunk;
+pub mod command;
 pub mod info;
 pub mod io;
 pub mod metadata;
+pub mod nbt;
 pub mod packets;
 pub mod player_info;
+pub mod scoreboard;
 pub mod types;
 mod variable;
 
@@ -230,6 +241,9 @@ pub enum FieldReadError {
 pub trait Packet: Sized {
     fn read_from(buffer: &mut dyn Read) -> Result<Self, ReadError>;
     fn write_to(&self, buffer: &mut dyn Write) -> Result<(), WriteError>;
+
+    /// The name of the packet, without any of its fields.
+    fn name(&self) -> &'static str;
 }
 
 pub trait PacketField: Sized {
@@ -177,12 +177,12 @@ indices!(
     20 => RightShoulderEntity,
 );
 
-#[derive(Debug)]
+#[derive(Debug, Default)]
 pub struct EntityMetadata(HashMap<u8, MetaType>);
 
 impl EntityMetadata {
     pub fn new() -> EntityMetadata {
-        EntityMetadata(HashMap::new())
+        EntityMetadata::default()
     }
 
     pub fn with(mut self, index: MetaIndex, value: MetaType) -> EntityMetadata {
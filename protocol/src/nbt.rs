@@ -0,0 +1,337 @@
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+use byteorder::{BigEndian, ReadBytesExt, WriteBytesExt};
+
+use crate::{FieldReadError, FieldWriteError, PacketField};
+
+pub type Compound = HashMap<String, Nbt>;
+
+/// The most elements allocated ahead of reading a list or array. Lengths are read from the data,
+/// so they can't be trusted to allocate all at once.
+const MAX_PREALLOCATION: usize = 4096;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Nbt {
+    Byte(i8),
+    Short(i16),
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    ByteArray(Vec<u8>),
+    String(String),
+    List(Vec<Nbt>),
+    Compound(Compound),
+    IntArray(Vec<i32>),
+    LongArray(Vec<i64>),
+}
+
+impl Nbt {
+    const END: u8 = 0;
+
+    fn id(&self) -> u8 {
+        match self {
+            Nbt::Byte(_) => 1,
+            Nbt::Short(_) => 2,
+            Nbt::Int(_) => 3,
+            Nbt::Long(_) => 4,
+            Nbt::Float(_) => 5,
+            Nbt::Double(_) => 6,
+            Nbt::ByteArray(_) => 7,
+            Nbt::String(_) => 8,
+            Nbt::List(_) => 9,
+            Nbt::Compound(_) => 10,
+            Nbt::IntArray(_) => 11,
+            Nbt::LongArray(_) => 12,
+        }
+    }
+
+    /// Reads a named root tag, as found in files and most packets.
+    pub fn read_named(buffer: &mut dyn Read) -> Result<(String, Nbt), FieldReadError> {
+        let id = buffer.read_u8()?;
+        if id == Nbt::END {
+            return Err(FieldReadError::InvalidEnumId(format!("{}", id)));
+        }
+
+        let name = read_string(buffer)?;
+        Ok((name, Nbt::read_payload(id, buffer)?))
+    }
+
+    pub fn write_named(&self, name: &str, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        buffer.write_u8(self.id())?;
+        write_string(name, buffer)?;
+        self.write_payload(buffer)
+    }
+
+    fn read_payload(id: u8, buffer: &mut dyn Read) -> Result<Nbt, FieldReadError> {
+        Ok(match id {
+            1 => Nbt::Byte(buffer.read_i8()?),
+            2 => Nbt::Short(buffer.read_i16::<BigEndian>()?),
+            3 => Nbt::Int(buffer.read_i32::<BigEndian>()?),
+            4 => Nbt::Long(buffer.read_i64::<BigEndian>()?),
+            5 => Nbt::Float(buffer.read_f32::<BigEndian>()?),
+            6 => Nbt::Double(buffer.read_f64::<BigEndian>()?),
+            7 => {
+                let length = read_length(buffer)?;
+                let mut bytes = Vec::with_capacity(length.min(MAX_PREALLOCATION));
+                buffer.take(length as u64).read_to_end(&mut bytes)?;
+                if bytes.len() < length {
+                    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+                }
+
+                Nbt::ByteArray(bytes)
+            }
+            8 => Nbt::String(read_string(buffer)?),
+            9 => {
+                let id = buffer.read_u8()?;
+                let length = read_length(buffer)?;
+                let mut list = Vec::with_capacity(length.min(MAX_PREALLOCATION));
+                for _ in 0..length {
+                    list.push(Nbt::read_payload(id, buffer)?);
+                }
+
+                Nbt::List(list)
+            }
+            10 => {
+                let mut compound = Compound::new();
+                loop {
+                    let id = buffer.read_u8()?;
+                    if id == Nbt::END {
+                        break;
+                    }
+
+                    let name = read_string(buffer)?;
+                    compound.insert(name, Nbt::read_payload(id, buffer)?);
+                }
+
+                Nbt::Compound(compound)
+            }
+            11 => {
+                let length = read_length(buffer)?;
+                let mut array = Vec::with_capacity(length.min(MAX_PREALLOCATION));
+                for _ in 0..length {
+                    array.push(buffer.read_i32::<BigEndian>()?);
+                }
+
+                Nbt::IntArray(array)
+            }
+            12 => {
+                let length = read_length(buffer)?;
+                let mut array = Vec::with_capacity(length.min(MAX_PREALLOCATION));
+                for _ in 0..length {
+                    array.push(buffer.read_i64::<BigEndian>()?);
+                }
+
+                Nbt::LongArray(array)
+            }
+            id => return Err(FieldReadError::InvalidEnumId(format!("{}", id))),
+        })
+    }
+
+    fn write_payload(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            Nbt::Byte(value) => buffer.write_i8(*value)?,
+            Nbt::Short(value) => buffer.write_i16::<BigEndian>(*value)?,
+            Nbt::Int(value) => buffer.write_i32::<BigEndian>(*value)?,
+            Nbt::Long(value) => buffer.write_i64::<BigEndian>(*value)?,
+            Nbt::Float(value) => buffer.write_f32::<BigEndian>(*value)?,
+            Nbt::Double(value) => buffer.write_f64::<BigEndian>(*value)?,
+            Nbt::ByteArray(bytes) => {
+                buffer.write_i32::<BigEndian>(bytes.len() as i32)?;
+                buffer.write_all(bytes)?;
+            }
+            Nbt::String(value) => write_string(value, buffer)?,
+            Nbt::List(list) => {
+                buffer.write_u8(list.first().map(Nbt::id).unwrap_or(Nbt::END))?;
+                buffer.write_i32::<BigEndian>(list.len() as i32)?;
+                for element in list {
+                    element.write_payload(buffer)?;
+                }
+            }
+            Nbt::Compound(compound) => {
+                for (name, value) in compound {
+                    value.write_named(name, buffer)?;
+                }
+
+                buffer.write_u8(Nbt::END)?;
+            }
+            Nbt::IntArray(array) => {
+                buffer.write_i32::<BigEndian>(array.len() as i32)?;
+                for value in array {
+                    buffer.write_i32::<BigEndian>(*value)?;
+                }
+            }
+            Nbt::LongArray(array) => {
+                buffer.write_i32::<BigEndian>(array.len() as i32)?;
+                for value in array {
+                    buffer.write_i64::<BigEndian>(*value)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<&Nbt> {
+        self.as_compound().and_then(|compound| compound.get(key))
+    }
+
+    pub fn as_compound(&self) -> Option<&Compound> {
+        match self {
+            Nbt::Compound(compound) => Some(compound),
+            _ => None,
+        }
+    }
+
+    pub fn as_list(&self) -> Option<&[Nbt]> {
+        match self {
+            Nbt::List(list) => Some(list),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Nbt::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            Nbt::ByteArray(bytes) => Some(bytes),
+            _ => None,
+        }
+    }
+
+    pub fn as_int_array(&self) -> Option<&[i32]> {
+        match self {
+            Nbt::IntArray(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_long_array(&self) -> Option<&[i64]> {
+        match self {
+            Nbt::LongArray(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    /// Returns any integral tag as an `i64`, since files aren't always consistent in which
+    /// width they use for the same value.
+    pub fn as_i64(&self) -> Option<i64> {
+        match self {
+            Nbt::Byte(value) => Some(*value as i64),
+            Nbt::Short(value) => Some(*value as i64),
+            Nbt::Int(value) => Some(*value as i64),
+            Nbt::Long(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Nbt::Float(value) => Some(*value as f64),
+            Nbt::Double(value) => Some(*value),
+            _ => self.as_i64().map(|value| value as f64),
+        }
+    }
+}
+
+fn read_length(buffer: &mut dyn Read) -> Result<usize, FieldReadError> {
+    let length = buffer.read_i32::<BigEndian>()?;
+    if length < 0 {
+        return Err(FieldReadError::VariableTooLarge);
+    }
+
+    Ok(length as usize)
+}
+
+fn read_string(buffer: &mut dyn Read) -> Result<String, FieldReadError> {
+    let length = buffer.read_u16::<BigEndian>()?;
+    let mut bytes = vec![0; length as usize];
+    buffer.read_exact(&mut bytes)?;
+    Ok(String::from_utf8(bytes)?)
+}
+
+fn write_string(value: &str, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+    buffer.write_u16::<BigEndian>(value.len() as u16)?;
+    buffer.write_all(value.as_bytes())?;
+    Ok(())
+}
+
+impl From<Compound> for Nbt {
+    fn from(compound: Compound) -> Nbt {
+        Nbt::Compound(compound)
+    }
+}
+
+impl PacketField for Nbt {
+    fn read_from(buffer: &mut dyn Read) -> Result<Nbt, FieldReadError> {
+        Ok(Nbt::read_named(buffer)?.1)
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        self.write_named("", buffer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let mut inner = Compound::new();
+        inner.insert("name".to_string(), Nbt::String("stone".to_string()));
+        inner.insert("empty".to_string(), Nbt::List(Vec::new()));
+
+        let mut root = Compound::new();
+        root.insert("byte".to_string(), Nbt::Byte(-1));
+        root.insert("short".to_string(), Nbt::Short(-300));
+        root.insert("int".to_string(), Nbt::Int(1 << 20));
+        root.insert("long".to_string(), Nbt::Long(i64::MIN));
+        root.insert("float".to_string(), Nbt::Float(0.5));
+        root.insert("double".to_string(), Nbt::Double(-2.25));
+        root.insert("bytes".to_string(), Nbt::ByteArray(vec![0, 1, 255]));
+        root.insert("string".to_string(), Nbt::String("héllo".to_string()));
+        root.insert(
+            "list".to_string(),
+            Nbt::List(vec![Nbt::Compound(inner.clone()), Nbt::Compound(inner)]),
+        );
+        root.insert("ints".to_string(), Nbt::IntArray(vec![-1, 0, i32::MAX]));
+        root.insert("longs".to_string(), Nbt::LongArray(vec![i64::MAX, 7]));
+        let root = Nbt::Compound(root);
+
+        let mut buffer = Vec::new();
+        root.write_named("root", &mut buffer).unwrap();
+
+        let (name, read) = Nbt::read_named(&mut &buffer[..]).unwrap();
+        assert_eq!(name, "root");
+        assert_eq!(read, root);
+    }
+
+    #[test]
+    fn rejects_negative_lengths() {
+        let buffer = [7, 0, 0, 0xff, 0xff, 0xff, 0xff];
+        assert!(Nbt::read_named(&mut &buffer[..]).is_err());
+    }
+
+    #[test]
+    fn rejects_lengths_past_the_end() {
+        for id in [7, 9, 11, 12] {
+            let mut buffer = vec![id, 0, 0];
+            if id == 9 {
+                buffer.push(4);
+            }
+            buffer.extend_from_slice(&i32::MAX.to_be_bytes());
+            buffer.extend_from_slice(&[0; 16]);
+
+            assert!(Nbt::read_named(&mut &buffer[..]).is_err());
+        }
+    }
+}
@@ -33,4 +33,13 @@ impl ClientPacket {
             State::Play => Ok(ClientPacket::Play(ClientPlayPacket::read_from(buffer)?)),
         }
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ClientPacket::Handshake(packet) => packet.name(),
+            ClientPacket::Status(packet) => packet.name(),
+            ClientPacket::Login(packet) => packet.name(),
+            ClientPacket::Play(packet) => packet.name(),
+        }
+    }
 }
@@ -6,6 +6,9 @@ packet! {
         0x00 = TeleportConfirm {
             id: VarInt,
         },
+        0x03 = ChatMessage {
+            message: String,
+        },
         0x05 = ClientSettings {
             locale: String,
             view_distance: i8,
@@ -16,6 +19,10 @@ packet! {
             text_filtering: bool,
             allow_server_listings: bool,
         },
+        0x06 = TabComplete {
+            id: VarInt,
+            text: String,
+        },
         0x0a = PluginMessage {
             channel: String,
             data: RawBytes,
@@ -37,5 +44,19 @@ packet! {
             pitch: f32,
             on_ground: bool,
         },
+        0x13 = PlayerRotation {
+            yaw: f32,
+            pitch: f32,
+            on_ground: bool,
+        },
+        0x14 = PlayerMovement {
+            on_ground: bool,
+        },
+        0x19 = PlayerAbilities {
+            flags: u8,
+        },
+        0x2c = Animation {
+            hand: VarInt,
+        },
     }
 }
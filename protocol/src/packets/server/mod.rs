@@ -25,4 +25,12 @@ impl ServerPacket {
 
         Ok(())
     }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ServerPacket::Status(packet) => packet.name(),
+            ServerPacket::Login(packet) => packet.name(),
+            ServerPacket::Play(packet) => packet.name(),
+        }
+    }
 }
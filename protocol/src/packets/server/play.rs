@@ -1,22 +1,70 @@
+use uuid::Uuid;
+
 use crate::{
-    chat::Message,
+    boss_bar::BossBarAction,
+    chat::{ChatPosition, Message},
+    chunk::{BlockEntity, LightData},
+    command::{CommandNode, TabCompleteMatch},
     io::{RawBytes, VarIntPrefixedVec},
     metadata::EntityMetadata,
+    nbt::Nbt,
     player_info::PlayerInfo,
-    types::{GameMode, Position},
-    VarInt,
+    scoreboard::{DisplaySlot, ObjectiveAction, ScoreAction, TeamAction},
+    types::{Abilities, GameMode, GameStateChange, Position},
+    VarInt, VarLong,
 };
 
 packet! {
     #[derive(Debug)]
     pub enum ServerPlayPacket {
+        0x0d = BossBar {
+            uuid: Uuid,
+            action: BossBarAction,
+        },
+        0x0f = ChatMessage {
+            message: Message,
+            position: ChatPosition,
+            sender: Uuid,
+        },
+        0x11 = TabComplete {
+            id: VarInt,
+            start: VarInt,
+            length: VarInt,
+            matches: VarIntPrefixedVec<TabCompleteMatch>,
+        },
+        0x12 = DeclareCommands {
+            nodes: VarIntPrefixedVec<CommandNode>,
+            root_index: VarInt,
+        },
         0x18 = PluginMessage {
             channel: String,
             data: RawBytes,
         },
+        0x1e = ChangeGameState {
+            reason: GameStateChange,
+            value: f32,
+        },
+        0x20 = InitializeWorldBorder {
+            x: f64,
+            z: f64,
+            old_diameter: f64,
+            new_diameter: f64,
+            speed: VarLong,
+            portal_teleport_boundary: VarInt,
+            warning_blocks: VarInt,
+            warning_time: VarInt,
+        },
         0x21 = KeepAlive {
             id: u64,
         },
+        0x22 = ChunkData {
+            chunk_x: i32,
+            chunk_z: i32,
+            heightmaps: Nbt,
+            data: VarIntPrefixedVec<u8>,
+            block_entities: VarIntPrefixedVec<BlockEntity>,
+            light: LightData,
+        },
         0x26 = JoinGame {
             entity_id: i32,
             hardcore: bool,
@@ -39,6 +87,11 @@ packet! {
         0x1a = Disconnect {
             reason: Message,
         },
+        0x32 = PlayerAbilities {
+            abilities: Abilities,
+            flying_speed: f32,
+            walking_speed: f32,
+        },
         0x36 = PlayerInfo {
             info: PlayerInfo,
         },
@@ -52,13 +105,55 @@ packet! {
             teleport_id: VarInt,
             dismount_vehicle: bool,
         },
+        0x41 = ActionBar {
+            text: Message,
+        },
+        0x49 = UpdateViewPosition {
+            chunk_x: VarInt,
+            chunk_z: VarInt,
+        },
         0x4b = SpawnPosition {
             location: Position,
             angle: f32,
         },
+        0x4c = DisplayScoreboard {
+            position: DisplaySlot,
+            score_name: String,
+        },
         0x4d = EntityMetadata {
             id: VarInt,
             metadata: EntityMetadata,
         },
+        0x53 = ScoreboardObjective {
+            objective_name: String,
+            action: ObjectiveAction,
+        },
+        0x55 = Teams {
+            team_name: String,
+            action: TeamAction,
+        },
+        0x56 = UpdateScore {
+            entity_name: String,
+            action: ScoreAction,
+        },
+        0x58 = SetTitleSubtitle {
+            text: Message,
+        },
+        0x59 = TimeUpdate {
+            world_age: i64,
+            time_of_day: i64,
+        },
+        0x5a = SetTitleText {
+            text: Message,
+        },
+        0x5b = SetTitleTimes {
+            fade_in: i32,
+            stay: i32,
+            fade_out: i32,
+        },
+        0x5f = PlayerListHeaderAndFooter {
+            header: Message,
+            footer: Message,
+        },
     }
 }
@@ -1,10 +1,9 @@
-use crate::info::ServerInfo;
-
 packet! {
     #[derive(Debug)]
     pub enum ServerStatusPacket {
         0x00 = Response {
-            response: ServerInfo,
+            // The JSON of a `ServerInfo`, which can be rendered ahead of time.
+            response: String,
         },
         0x01 = Pong {
             payload: i64,
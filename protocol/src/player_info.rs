@@ -12,10 +12,14 @@ use crate::{
 #[derive(Debug)]
 pub enum PlayerInfo {
     AddPlayer(VarIntPrefixedVec<AddPlayerAction>),
+    UpdateGameMode(VarIntPrefixedVec<UpdateGameModeAction>),
+    UpdateLatency(VarIntPrefixedVec<UpdateLatencyAction>),
+    UpdateDisplayName(VarIntPrefixedVec<UpdateDisplayNameAction>),
+    RemovePlayer(VarIntPrefixedVec<Uuid>),
 }
 
 packet_field! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     pub struct AddPlayerAction {
         pub uuid: Uuid,
         pub name: String,
@@ -27,7 +31,7 @@ packet_field! {
 }
 
 packet_field! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone)]
     pub struct AddPlayerProperty {
         pub name: String,
         pub value: String,
@@ -35,22 +39,149 @@ packet_field! {
     }
 }
 
+packet_field! {
+    #[derive(Debug)]
+    pub struct UpdateGameModeAction {
+        pub uuid: Uuid,
+        pub game_mode: GameMode,
+    }
+}
+
+packet_field! {
+    #[derive(Debug)]
+    pub struct UpdateLatencyAction {
+        pub uuid: Uuid,
+        pub ping: VarInt,
+    }
+}
+
+packet_field! {
+    #[derive(Debug)]
+    pub struct UpdateDisplayNameAction {
+        pub uuid: Uuid,
+        pub display_name: BooleanPrefixedOption<Message>,
+    }
+}
+
 impl PacketField for PlayerInfo {
     fn read_from(buffer: &mut dyn Read) -> Result<PlayerInfo, FieldReadError> {
         match VarInt::read_from(buffer)? {
             VarInt(0) => Ok(PlayerInfo::AddPlayer(VarIntPrefixedVec::read_from(buffer)?)),
+            VarInt(1) => Ok(PlayerInfo::UpdateGameMode(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
+            VarInt(2) => Ok(PlayerInfo::UpdateLatency(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
+            VarInt(3) => Ok(PlayerInfo::UpdateDisplayName(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
+            VarInt(4) => Ok(PlayerInfo::RemovePlayer(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
             id => Err(FieldReadError::InvalidEnumId(format!("{:?}", id))),
         }
     }
 
     fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
         match self {
-            PlayerInfo::AddPlayer(action) => {
+            PlayerInfo::AddPlayer(actions) => {
                 VarInt(0).write_to(buffer)?;
-                action.write_to(buffer)?;
+                actions.write_to(buffer)?;
+            }
+            PlayerInfo::UpdateGameMode(actions) => {
+                VarInt(1).write_to(buffer)?;
+                actions.write_to(buffer)?;
+            }
+            PlayerInfo::UpdateLatency(actions) => {
+                VarInt(2).write_to(buffer)?;
+                actions.write_to(buffer)?;
+            }
+            PlayerInfo::UpdateDisplayName(actions) => {
+                VarInt(3).write_to(buffer)?;
+                actions.write_to(buffer)?;
+            }
+            PlayerInfo::RemovePlayer(uuids) => {
+                VarInt(4).write_to(buffer)?;
+                uuids.write_to(buffer)?;
             }
         }
 
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const UUID: Uuid = Uuid::from_u128(0x069a79f4_44e9_4726_a5be_fca90e38aaf5);
+
+    fn round_trip(info: PlayerInfo) {
+        let buffer = info.write_to_vec().unwrap();
+        let read = PlayerInfo::read_from(&mut &buffer[..]).unwrap();
+
+        assert_eq!(format!("{:?}", read), format!("{:?}", info));
+        assert_eq!(read.write_to_vec().unwrap(), buffer);
+    }
+
+    #[test]
+    fn add_player_round_trip() {
+        round_trip(PlayerInfo::AddPlayer(VarIntPrefixedVec(vec![
+            AddPlayerAction {
+                uuid: UUID,
+                name: "Notch".to_string(),
+                properties: VarIntPrefixedVec(vec![AddPlayerProperty {
+                    name: "textures".to_string(),
+                    value: "e30=".to_string(),
+                    signature: BooleanPrefixedOption(Some("c2ln".to_string())),
+                }]),
+                game_mode: GameMode::Creative,
+                ping: VarInt(42),
+                display_name: BooleanPrefixedOption(Some(Message::new("Notch"))),
+            },
+        ])));
+    }
+
+    #[test]
+    fn update_game_mode_round_trip() {
+        round_trip(PlayerInfo::UpdateGameMode(VarIntPrefixedVec(vec![
+            UpdateGameModeAction {
+                uuid: UUID,
+                game_mode: GameMode::Spectator,
+            },
+        ])));
+    }
+
+    #[test]
+    fn update_latency_round_trip() {
+        round_trip(PlayerInfo::UpdateLatency(VarIntPrefixedVec(vec![
+            UpdateLatencyAction {
+                uuid: UUID,
+                ping: VarInt(150),
+            },
+        ])));
+    }
+
+    #[test]
+    fn update_display_name_round_trip() {
+        round_trip(PlayerInfo::UpdateDisplayName(VarIntPrefixedVec(vec![
+            UpdateDisplayNameAction {
+                uuid: UUID,
+                display_name: BooleanPrefixedOption(Some(Message::new("Away"))),
+            },
+            UpdateDisplayNameAction {
+                uuid: Uuid::nil(),
+                display_name: BooleanPrefixedOption(None),
+            },
+        ])));
+    }
+
+    #[test]
+    fn remove_player_round_trip() {
+        round_trip(PlayerInfo::RemovePlayer(VarIntPrefixedVec(vec![
+            UUID,
+            Uuid::nil(),
+        ])));
+    }
+}
@@ -0,0 +1,193 @@
+use std::io::{Read, Write};
+
+use crate::{
+    chat::Message, io::VarIntPrefixedVec, FieldReadError, FieldWriteError, PacketField, VarInt,
+};
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy)]
+    pub enum DisplaySlot: i8 {
+        List = 0,
+        Sidebar = 1,
+        BelowName = 2,
+    }
+}
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy)]
+    pub enum ObjectiveKind: VarInt {
+        Integer = VarInt(0),
+        Hearts = VarInt(1),
+    }
+}
+
+#[derive(Debug)]
+pub enum ObjectiveAction {
+    Create {
+        display_name: Message,
+        kind: ObjectiveKind,
+    },
+    Remove,
+    Update {
+        display_name: Message,
+        kind: ObjectiveKind,
+    },
+}
+
+impl PacketField for ObjectiveAction {
+    fn read_from(buffer: &mut dyn Read) -> Result<ObjectiveAction, FieldReadError> {
+        match i8::read_from(buffer)? {
+            0 => Ok(ObjectiveAction::Create {
+                display_name: Message::read_from(buffer)?,
+                kind: ObjectiveKind::read_from(buffer)?,
+            }),
+            1 => Ok(ObjectiveAction::Remove),
+            2 => Ok(ObjectiveAction::Update {
+                display_name: Message::read_from(buffer)?,
+                kind: ObjectiveKind::read_from(buffer)?,
+            }),
+            id => Err(FieldReadError::InvalidEnumId(format!("{:?}", id))),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            ObjectiveAction::Create { display_name, kind } => {
+                0i8.write_to(buffer)?;
+                display_name.write_to(buffer)?;
+                kind.write_to(buffer)?;
+            }
+            ObjectiveAction::Remove => 1i8.write_to(buffer)?,
+            ObjectiveAction::Update { display_name, kind } => {
+                2i8.write_to(buffer)?;
+                display_name.write_to(buffer)?;
+                kind.write_to(buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+packet_field! {
+    #[derive(Debug)]
+    pub struct TeamInfo {
+        pub display_name: Message,
+        pub friendly_flags: i8,
+        pub name_tag_visibility: String,
+        pub collision_rule: String,
+        pub color: VarInt,
+        pub prefix: Message,
+        pub suffix: Message,
+    }
+}
+
+impl TeamInfo {
+    /// A team that only exists to show a prefix and suffix around its entries.
+    pub fn text(prefix: Message, suffix: Message) -> TeamInfo {
+        TeamInfo {
+            display_name: Message::new(""),
+            friendly_flags: 0,
+            name_tag_visibility: "always".to_string(),
+            collision_rule: "always".to_string(),
+            // Reset, which leaves the entry names uncolored.
+            color: VarInt(21),
+            prefix,
+            suffix,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum TeamAction {
+    Create {
+        info: TeamInfo,
+        entities: VarIntPrefixedVec<String>,
+    },
+    Remove,
+    UpdateInfo(TeamInfo),
+    AddEntities(VarIntPrefixedVec<String>),
+    RemoveEntities(VarIntPrefixedVec<String>),
+}
+
+impl PacketField for TeamAction {
+    fn read_from(buffer: &mut dyn Read) -> Result<TeamAction, FieldReadError> {
+        match i8::read_from(buffer)? {
+            0 => Ok(TeamAction::Create {
+                info: TeamInfo::read_from(buffer)?,
+                entities: VarIntPrefixedVec::read_from(buffer)?,
+            }),
+            1 => Ok(TeamAction::Remove),
+            2 => Ok(TeamAction::UpdateInfo(TeamInfo::read_from(buffer)?)),
+            3 => Ok(TeamAction::AddEntities(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
+            4 => Ok(TeamAction::RemoveEntities(VarIntPrefixedVec::read_from(
+                buffer,
+            )?)),
+            id => Err(FieldReadError::InvalidEnumId(format!("{:?}", id))),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            TeamAction::Create { info, entities } => {
+                0i8.write_to(buffer)?;
+                info.write_to(buffer)?;
+                entities.write_to(buffer)?;
+            }
+            TeamAction::Remove => 1i8.write_to(buffer)?,
+            TeamAction::UpdateInfo(info) => {
+                2i8.write_to(buffer)?;
+                info.write_to(buffer)?;
+            }
+            TeamAction::AddEntities(entities) => {
+                3i8.write_to(buffer)?;
+                entities.write_to(buffer)?;
+            }
+            TeamAction::RemoveEntities(entities) => {
+                4i8.write_to(buffer)?;
+                entities.write_to(buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum ScoreAction {
+    Update { objective: String, value: VarInt },
+    Remove { objective: String },
+}
+
+impl PacketField for ScoreAction {
+    fn read_from(buffer: &mut dyn Read) -> Result<ScoreAction, FieldReadError> {
+        match i8::read_from(buffer)? {
+            0 => Ok(ScoreAction::Update {
+                objective: String::read_from(buffer)?,
+                value: VarInt::read_from(buffer)?,
+            }),
+            1 => Ok(ScoreAction::Remove {
+                objective: String::read_from(buffer)?,
+            }),
+            id => Err(FieldReadError::InvalidEnumId(format!("{:?}", id))),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            ScoreAction::Update { objective, value } => {
+                0i8.write_to(buffer)?;
+                objective.write_to(buffer)?;
+                value.write_to(buffer)?;
+            }
+            ScoreAction::Remove { objective } => {
+                1i8.write_to(buffer)?;
+                objective.write_to(buffer)?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -1,9 +1,12 @@
 use std::io::{Read, Write};
 
+use serde::{Deserialize, Serialize};
+
 use crate::{FieldReadError, FieldWriteError, PacketField, VarInt};
 
 packet_enum! {
-    #[derive(Debug, Clone, Copy)]
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(rename_all = "lowercase")]
     pub enum GameMode: i8 {
         Survival = 0,
         Creative = 1,
@@ -77,3 +80,60 @@ packet_enum! {
         Dying = VarInt(7),
     }
 }
+
+packet_enum! {
+    #[derive(Debug, Clone, Copy)]
+    pub enum GameStateChange: u8 {
+        NoRespawnBlock = 0,
+        EndRaining = 1,
+        BeginRaining = 2,
+        ChangeGameMode = 3,
+        WinGame = 4,
+        DemoEvent = 5,
+        ArrowHitPlayer = 6,
+        RainLevelChange = 7,
+        ThunderLevelChange = 8,
+        PufferfishSting = 9,
+        ElderGuardianAppearance = 10,
+        EnableRespawnScreen = 11,
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Abilities {
+    pub invulnerable: bool,
+    pub flying: bool,
+    pub allow_flying: bool,
+    pub instant_break: bool,
+}
+
+impl PacketField for Abilities {
+    fn read_from(buffer: &mut dyn Read) -> Result<Abilities, FieldReadError> {
+        let flags = u8::read_from(buffer)?;
+        Ok(Abilities {
+            invulnerable: flags & 0x01 != 0,
+            flying: flags & 0x02 != 0,
+            allow_flying: flags & 0x04 != 0,
+            instant_break: flags & 0x08 != 0,
+        })
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        let mut flags = 0u8;
+        for (i, flag) in [
+            self.invulnerable,
+            self.flying,
+            self.allow_flying,
+            self.instant_break,
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            if flag {
+                flags |= 1 << i;
+            }
+        }
+
+        flags.write_to(buffer)
+    }
+}
@@ -43,10 +43,55 @@ impl PacketField for VarInt {
             let part = value as u8;
             value >>= 7;
             if value == 0 {
-                buffer.write_u8(part as u8 & 0x7f)?;
+                buffer.write_u8(part & 0x7f)?;
                 break Ok(());
             } else {
-                buffer.write_u8(part as u8 | 0x80)?;
+                buffer.write_u8(part | 0x80)?;
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct VarLong(pub i64);
+
+impl Display for VarLong {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl PacketField for VarLong {
+    fn read_from(buffer: &mut dyn Read) -> Result<Self, FieldReadError> {
+        let mut value = 0;
+        let mut length = 0;
+
+        loop {
+            let byte = buffer.read_u8()?;
+            value |= ((byte & 0x7f) as i64) << (length * 7);
+            length += 1;
+
+            if length > 10 {
+                break Err(FieldReadError::VariableTooLarge);
+            }
+
+            if (byte & 0x80) == 0 {
+                break Ok(VarLong(value));
+            }
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        let mut value = self.0 as u64;
+
+        loop {
+            let part = value as u8;
+            value >>= 7;
+            if value == 0 {
+                buffer.write_u8(part & 0x7f)?;
+                break Ok(());
+            } else {
+                buffer.write_u8(part | 0x80)?;
             }
         }
     }
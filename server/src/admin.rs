@@ -0,0 +1,130 @@
+use anyhow::anyhow;
+use log::{info, warn};
+use protocol::{
+    chat::{ChatPosition, Message},
+    packets::server::{play::ServerPlayPacket, ServerPacket},
+};
+use uuid::Uuid;
+
+use crate::{bans::Ban, client::ClientEvent, reload, state::ServerState};
+
+const HELP: &str = "\
+Available commands:
+  list                      Lists the players online
+  kick <player> [reason]    Kicks a player
+  broadcast <message>       Sends a message to every player
+  ban <player> [reason]     Bans a player and kicks them if they're online
+  unban <player>            Lifts the ban of a player
+  reload                    Reloads the config file
+  stop                      Stops the server";
+
+/// Runs a command typed by an administrator, returning the text to show them.
+pub async fn execute(state: &ServerState, input: &str) -> String {
+    let mut parts = input.trim().splitn(2, char::is_whitespace);
+    let command = parts.next().unwrap_or_default().to_lowercase();
+    let args = parts.next().unwrap_or_default().trim();
+
+    let (player, reason) = match args.split_once(char::is_whitespace) {
+        Some((player, reason)) => (player, Some(reason.trim())),
+        None => (args, None),
+    };
+
+    match command.as_str() {
+        "help" | "?" => HELP.to_string(),
+        "list" => {
+            let players = state.players.all();
+            let names = players
+                .iter()
+                .map(|player| player.name.as_str())
+                .collect::<Vec<_>>();
+
+            if names.is_empty() {
+                "There are no players online".to_string()
+            } else {
+                format!(
+                    "There are {} players online: {}",
+                    names.len(),
+                    names.join(", ")
+                )
+            }
+        }
+        "kick" if !player.is_empty() => match state.players.find(player) {
+            Some(handle) => {
+                let reason = reason.unwrap_or("You have been kicked.").to_string();
+                if handle.send(ClientEvent::Disconnect(reason)) {
+                    format!("Kicked {}", handle.name)
+                } else {
+                    format!("Failed to kick {}, player is not responding", handle.name)
+                }
+            }
+            None => format!("{} is not online", player),
+        },
+        "broadcast" if !args.is_empty() => {
+            broadcast(state, args);
+            format!("Sent message to {} players", state.players.len())
+        }
+        "ban" if !player.is_empty() => {
+            let reason = reason
+                .unwrap_or("You are banned from this server.")
+                .to_string();
+            let online = state.players.find(player);
+
+            let ban = Ban {
+                name: online
+                    .as_ref()
+                    .map_or_else(|| player.to_string(), |handle| handle.name.clone()),
+                uuid: online.as_ref().map(|handle| handle.uuid),
+                reason: reason.clone(),
+            };
+
+            if let Err(err) = state.bans.add(ban) {
+                warn!("failed to save ban list: {:#}", anyhow!(err));
+            }
+
+            // The ban is saved either way, so the player can't rejoin once the connection drops.
+            match online {
+                Some(handle) if !handle.send(ClientEvent::Disconnect(reason)) => format!(
+                    "Banned {}, but failed to kick them, player is not responding",
+                    handle.name
+                ),
+                _ => format!("Banned {}", player),
+            }
+        }
+        "unban" | "pardon" if !player.is_empty() => match state.bans.remove(player) {
+            Ok(true) => format!("Unbanned {}", player),
+            Ok(false) => format!("{} is not banned", player),
+            Err(err) => format!("Failed to save ban list: {:#}", anyhow!(err)),
+        },
+        "reload" => match reload::reload(state).await {
+            Ok(changes) if changes.is_empty() => {
+                "Reloaded the config file, nothing changed".to_string()
+            }
+            Ok(changes) => format!("Reloaded the config file, changed: {}", changes.join(", ")),
+            Err(err) => format!("Failed to reload the config file: {:#}", anyhow!(err)),
+        },
+        "stop" => {
+            state.stop.notify_one();
+            "Stopping the server".to_string()
+        }
+        "kick" | "broadcast" | "ban" | "unban" | "pardon" => {
+            "Missing arguments, type help for usage".to_string()
+        }
+        _ => format!(
+            "Unknown command {}, type help for a list of commands",
+            command
+        ),
+    }
+}
+
+/// Sends a system message to every online player.
+pub fn broadcast(state: &ServerState, message: &str) {
+    state.players.broadcast(|| {
+        ServerPacket::Play(ServerPlayPacket::ChatMessage {
+            message: Message::new(message.to_string()),
+            position: ChatPosition::System,
+            sender: Uuid::nil(),
+        })
+    });
+
+    info!("broadcast: {}", message);
+}
@@ -0,0 +1,302 @@
+use std::{
+    convert::Infallible,
+    net::SocketAddr,
+    sync::{atomic::Ordering, Arc},
+    time::UNIX_EPOCH,
+};
+
+use anyhow::anyhow;
+use hyper::{
+    body::HttpBody,
+    header::{AUTHORIZATION, CONTENT_TYPE},
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use log::{debug, error, info};
+use protocol::{
+    chat::{ChatPosition, Message},
+    info::VERSION,
+    packets::server::{play::ServerPlayPacket, ServerPacket},
+};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use subtle::ConstantTimeEq;
+use tokio::net::TcpListener;
+use uuid::Uuid;
+
+use crate::{admin, client::ClientEvent, reload, shutdown::Shutdown, state::ServerState};
+
+/// Request bodies are tiny, so anything bigger than this is refused.
+const MAX_BODY_LENGTH: u64 = 16 * 1024;
+
+#[derive(Serialize)]
+struct Player {
+    name: String,
+    uuid: Uuid,
+    address: SocketAddr,
+    ping: u32,
+    brand: Option<String>,
+    joined_at: u64,
+}
+
+#[derive(Serialize)]
+struct Status {
+    online: usize,
+    max_players: isize,
+    motd: String,
+    version: String,
+    protocol: usize,
+    backend_online: bool,
+}
+
+#[derive(Deserialize)]
+struct KickRequest {
+    reason: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct MessageRequest {
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct TransferRequest {
+    server: String,
+}
+
+#[derive(Serialize)]
+struct ErrorResponse {
+    error: String,
+}
+
+/// Serves a JSON API for automation, which only answers requests carrying the configured token.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let (host, port) = {
+        let config = state.config.read().await;
+        (config.api.host.clone(), config.api.port)
+    };
+
+    let listener = match TcpListener::bind((host.as_str(), port))
+        .await
+        .and_then(|listener| listener.into_std())
+    {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("failed to start api listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    let server = match hyper::Server::from_tcp(listener) {
+        Ok(server) => server,
+        Err(err) => {
+            error!("failed to start api listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    info!("listening on {}:{} for api requests", host, port);
+
+    let service = make_service_fn(move |_| {
+        let state = state.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                let state = state.clone();
+                async move { Ok::<_, Infallible>(serve(&state, request).await) }
+            }))
+        }
+    });
+    let server = server
+        .serve(service)
+        .with_graceful_shutdown(async move { shutdown.recv().await });
+
+    if let Err(err) = server.await {
+        error!("api listener failed: {:#}", anyhow!(err));
+    }
+
+    debug!("halting api listener");
+}
+
+async fn serve(state: &ServerState, request: Request<Body>) -> Response<Body> {
+    if !authorized(state, &request).await {
+        return error(StatusCode::UNAUTHORIZED, "invalid or missing token");
+    }
+
+    let method = request.method().clone();
+    let path = request.uri().path().to_string();
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>();
+
+    match (method, segments.as_slice()) {
+        (Method::GET, ["players"]) => {
+            let players = state
+                .players
+                .all()
+                .into_iter()
+                .map(|player| Player {
+                    ping: player.ping(),
+                    brand: player.brand(),
+                    joined_at: player
+                        .joined_at
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |time| time.as_secs()),
+                    name: player.name,
+                    uuid: player.uuid,
+                    address: player.address,
+                })
+                .collect::<Vec<_>>();
+
+            json(StatusCode::OK, &players)
+        }
+        (Method::POST, ["players", uuid, action]) => {
+            let player = match Uuid::parse_str(uuid) {
+                Ok(uuid) => match state.players.get(&uuid) {
+                    Some(player) => player,
+                    None => return error(StatusCode::NOT_FOUND, "player is not online"),
+                },
+                Err(_) => return error(StatusCode::BAD_REQUEST, "invalid uuid"),
+            };
+
+            let event = match *action {
+                "kick" => match body::<KickRequest>(request).await {
+                    Ok(KickRequest { reason }) => ClientEvent::Disconnect(
+                        reason.unwrap_or_else(|| "You have been kicked.".to_string()),
+                    ),
+                    Err(response) => return response,
+                },
+                "message" => match body::<MessageRequest>(request).await {
+                    Ok(MessageRequest { message }) => ClientEvent::Packet(Box::new(
+                        ServerPacket::Play(ServerPlayPacket::ChatMessage {
+                            message: Message::new(message),
+                            position: ChatPosition::System,
+                            sender: Uuid::nil(),
+                        }),
+                    )),
+                    Err(response) => return response,
+                },
+                "transfer" => match body::<TransferRequest>(request).await {
+                    Ok(TransferRequest { server }) => ClientEvent::Transfer(server),
+                    Err(response) => return response,
+                },
+                _ => return error(StatusCode::NOT_FOUND, "unknown endpoint"),
+            };
+
+            if player.send(event) {
+                empty(StatusCode::NO_CONTENT)
+            } else {
+                error(StatusCode::SERVICE_UNAVAILABLE, "player is not responding")
+            }
+        }
+        (Method::POST, ["broadcast"]) => match body::<MessageRequest>(request).await {
+            Ok(MessageRequest { message }) => {
+                admin::broadcast(state, &message);
+                empty(StatusCode::NO_CONTENT)
+            }
+            Err(response) => response,
+        },
+        (Method::POST, ["reload"]) => match reload::reload(state).await {
+            Ok(changes) => json(StatusCode::OK, &changes),
+            Err(err) => error(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                &format!("failed to reload the config file: {:#}", anyhow!(err)),
+            ),
+        },
+        (Method::GET, ["status"]) => {
+            let config = state.config.read().await;
+            let status = Status {
+                online: state.players.len(),
+                max_players: config.info.max_players,
+                motd: config.info.motd.clone(),
+                version: VERSION.name.to_string(),
+                protocol: VERSION.protocol,
+                backend_online: state.backend_online.load(Ordering::Relaxed),
+            };
+
+            json(StatusCode::OK, &status)
+        }
+        _ => error(StatusCode::NOT_FOUND, "unknown endpoint"),
+    }
+}
+
+async fn authorized(state: &ServerState, request: &Request<Body>) -> bool {
+    let token = &state.config.read().await.api.token;
+    let header = request
+        .headers()
+        .get(AUTHORIZATION)
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "));
+
+    // The comparison takes the same time no matter how much of the token is right, so it can't
+    // be guessed one character at a time.
+    match header {
+        Some(header) if !token.is_empty() => header.as_bytes().ct_eq(token.as_bytes()).into(),
+        _ => false,
+    }
+}
+
+/// Reads and parses a JSON request body, or returns the response to send back instead.
+async fn body<T: DeserializeOwned>(request: Request<Body>) -> Result<T, Response<Body>> {
+    let too_large = || error(StatusCode::PAYLOAD_TOO_LARGE, "body is too large");
+    if request
+        .body()
+        .size_hint()
+        .upper()
+        .is_some_and(|length| length > MAX_BODY_LENGTH)
+    {
+        return Err(too_large());
+    }
+
+    // Chunked bodies don't announce their length, so it's checked while reading as well.
+    let mut body = request.into_body();
+    let mut bytes = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk.map_err(|_| error(StatusCode::BAD_REQUEST, "failed to read body"))?;
+        if (bytes.len() + chunk.len()) as u64 > MAX_BODY_LENGTH {
+            return Err(too_large());
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    // An empty body is treated like an empty object, so fields that are optional can be left out
+    // altogether.
+    let bytes = if bytes.is_empty() {
+        &b"{}"[..]
+    } else {
+        &bytes[..]
+    };
+    serde_json::from_slice(bytes)
+        .map_err(|err| error(StatusCode::BAD_REQUEST, &format!("invalid body: {}", err)))
+}
+
+fn json<T: Serialize>(status: StatusCode, value: &T) -> Response<Body> {
+    match serde_json::to_vec(value) {
+        Ok(body) => Response::builder()
+            .status(status)
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap(),
+        Err(err) => {
+            error!("failed to serialize api response: {:#}", anyhow!(err));
+            empty(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+fn error(status: StatusCode, message: &str) -> Response<Body> {
+    json(
+        status,
+        &ErrorResponse {
+            error: message.to_string(),
+        },
+    )
+}
+
+fn empty(status: StatusCode) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .body(Body::empty())
+        .unwrap()
+}
@@ -0,0 +1,80 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::RwLock,
+};
+
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Ban {
+    pub name: String,
+    pub uuid: Option<Uuid>,
+    pub reason: String,
+}
+
+/// Players that aren't allowed to join, saved to a JSON file whenever it changes.
+pub struct BanList {
+    path: PathBuf,
+    bans: RwLock<Vec<Ban>>,
+}
+
+impl BanList {
+    pub fn load(path: &Path) -> std::io::Result<BanList> {
+        let bans = if path.exists() {
+            serde_json::from_slice(&std::fs::read(path)?)?
+        } else {
+            Vec::new()
+        };
+
+        Ok(BanList {
+            path: path.to_path_buf(),
+            bans: RwLock::new(bans),
+        })
+    }
+
+    pub fn empty(path: &Path) -> BanList {
+        BanList {
+            path: path.to_path_buf(),
+            bans: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Finds the ban of a player, by UUID or by name if they were banned while offline.
+    pub fn find(&self, uuid: &Uuid, name: &str) -> Option<Ban> {
+        self.bans
+            .read()
+            .unwrap()
+            .iter()
+            .find(|ban| match ban.uuid {
+                Some(ref id) => id == uuid,
+                None => ban.name.eq_ignore_ascii_case(name),
+            })
+            .cloned()
+    }
+
+    pub fn add(&self, ban: Ban) -> std::io::Result<()> {
+        let mut bans = self.bans.write().unwrap();
+        bans.retain(|other| !other.name.eq_ignore_ascii_case(&ban.name));
+        bans.push(ban);
+        self.save(&bans)
+    }
+
+    /// Removes the ban of a player by name, returning whether they were banned.
+    pub fn remove(&self, name: &str) -> std::io::Result<bool> {
+        let mut bans = self.bans.write().unwrap();
+        let count = bans.len();
+        bans.retain(|ban| !ban.name.eq_ignore_ascii_case(name));
+
+        if bans.len() == count {
+            return Ok(false);
+        }
+
+        self.save(&bans)?;
+        Ok(true)
+    }
+
+    fn save(&self, bans: &[Ban]) -> std::io::Result<()> {
+        std::fs::write(&self.path, serde_json::to_vec_pretty(bans)?)
+    }
+}
@@ -1,18 +1,18 @@
 use std::{
     fmt::Display,
-    sync::{
-        atomic::{AtomicUsize, Ordering},
-        Arc,
-    },
-    time::Duration,
+    net::SocketAddr,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 
 use anyhow::anyhow;
 use log::{debug, error, info, warn};
 use once_cell::sync::Lazy;
 use protocol::{
-    chat::Message,
-    info::{ServerInfo, ServerPlayerInfo, VERSION},
+    boss_bar::{BossBarAction, BossBarDivision},
+    chat::{ChatPosition, Message},
+    command::{self, TabCompleteMatch},
+    info::VERSION,
     io::{BooleanPrefixedOption, RawBytes, VarIntPrefixedVec},
     metadata::{EntityMetadata, MetaIndex, MetaType},
     packets::{
@@ -26,31 +26,49 @@ use protocol::{
         },
         State,
     },
-    player_info::{AddPlayerAction, AddPlayerProperty, PlayerInfo},
-    types::{GameMode, Position},
-    PacketField, ReadError, VarInt,
+    player_info::AddPlayerProperty,
+    types::{Abilities, GameMode, GameStateChange},
+    PacketField, ReadError, VarInt, VarLong,
 };
 use rand::{rngs::OsRng, Rng};
 use rsa::{PaddingScheme, PublicKeyParts, RsaPrivateKey};
 use thiserror::Error;
 use tokio::{
     select,
-    sync::{broadcast, mpsc, RwLock},
+    sync::{
+        broadcast::{self, error::RecvError},
+        mpsc,
+    },
     time,
 };
 use uuid::Uuid;
 
 use crate::{
-    config::Config,
+    commands::{self, CommandKind, CommandRegistry},
+    config::{ChatMode, Config, Weather},
     connection::{Connection, ReceiveError, SendError},
+    metrics,
     mojang::{self, AuthenticationResponse},
+    players::{self, PlayerHandle},
+    proxy::{ProxyMessage, PROXY_CHANNEL},
+    queue,
     shutdown::Shutdown,
+    sidebar::Sidebar,
+    state::ServerState,
+    tab_list,
+    tick::{Tick, TICKS_PER_SECOND},
+    vhost,
+    world::{Location, Spawn},
 };
 
 const KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(5);
 
-// TODO: Find a better place to store this variable.
-pub static ONLINE_PLAYERS: AtomicUsize = AtomicUsize::new(0);
+/// The largest world border the client accepts, used as the portal teleport boundary.
+const MAX_WORLD_BORDER: i32 = 29999984;
+
+// Boss bars only need to be unique per client.
+const WELCOME_BAR: Uuid = Uuid::from_u128(1);
+const QUEUE_BAR: Uuid = Uuid::from_u128(2);
 
 static UNIVERSAL_RSA: Lazy<RsaPrivateKey> = Lazy::new(|| {
     RsaPrivateKey::new(&mut OsRng, 1024).expect("failed to generate server RSA private key")
@@ -65,6 +83,15 @@ static UNIVERSAL_ENCODED_RSA_PUBLIC_KEY: Lazy<Vec<u8>> = Lazy::new(|| {
 
 static UNIVERSAL_VERIFY_TOKEN: Lazy<Vec<u8>> = Lazy::new(|| (0..4).map(|_| OsRng.gen()).collect());
 
+/// Something another part of the server wants a client to do.
+#[derive(Debug)]
+pub enum ClientEvent {
+    Packet(Box<ServerPacket>),
+    Disconnect(String),
+    Transfer(String),
+    KeepAlive,
+}
+
 #[derive(Debug, Error)]
 pub enum PacketProcessingError {
     #[error("failed to send response packet")]
@@ -72,55 +99,102 @@ pub enum PacketProcessingError {
 }
 
 pub struct Client {
-    config: Arc<RwLock<Config>>,
+    state: Arc<ServerState>,
+    ticks: broadcast::Receiver<Tick>,
 
     shutdown: Shutdown,
     _shutdown_done: mpsc::Sender<()>,
     stop: broadcast::Sender<()>,
 
     connection: Connection,
+    address: SocketAddr,
     disconnected: bool,
 
-    packet_send: mpsc::Sender<ServerPacket>,
-    packet_queue: mpsc::Receiver<ServerPacket>,
+    event_send: mpsc::Sender<ClientEvent>,
+    event_queue: mpsc::Receiver<ClientEvent>,
+    handle: Option<PlayerHandle>,
 
     name: Option<String>,
     uuid: Option<Uuid>,
+    weather: Option<Weather>,
+    vhost: Option<usize>,
+    spawn: Spawn,
+
+    location: Location,
+    next_teleport_id: i32,
+    pending_teleport: Option<i32>,
+    /// The ID of the last keep-alive and when it was sent, until the client answers it.
+    pending_keep_alive: Option<(u64, Instant)>,
+
+    queue_bar: bool,
+    sidebar: Sidebar,
+    tab_list_interval: u64,
+    sidebar_interval: u64,
+
+    last_activity: Instant,
+    idle_warned: bool,
+
+    last_chat: Option<Instant>,
 }
 
 impl Client {
     pub fn new(
         connection: Connection,
-        config: Arc<RwLock<Config>>,
+        address: SocketAddr,
+        state: Arc<ServerState>,
         shutdown: Shutdown,
         shutdown_done: mpsc::Sender<()>,
     ) -> Client {
-        let (sender, receiver) = mpsc::channel(5);
+        let (sender, receiver) = mpsc::channel(32);
+        let spawn = state.world.spawn.clone();
 
         Client {
-            config,
+            ticks: state.ticks.subscribe(),
+            state,
 
             shutdown,
             _shutdown_done: shutdown_done,
 
             connection,
+            address,
             disconnected: false,
 
-            packet_send: sender,
-            packet_queue: receiver,
+            event_send: sender,
+            event_queue: receiver,
+            handle: None,
             stop: broadcast::channel(1).0,
 
             name: None,
             uuid: None,
+            weather: None,
+            vhost: None,
+            spawn,
+
+            location: Location::default(),
+            next_teleport_id: 0,
+            pending_teleport: None,
+            pending_keep_alive: None,
+
+            queue_bar: false,
+            sidebar: Sidebar::default(),
+            // These are copied from the config every second, which always happens before the
+            // first time they're used.
+            tab_list_interval: TICKS_PER_SECOND,
+            sidebar_interval: TICKS_PER_SECOND,
+
+            last_activity: Instant::now(),
+            idle_warned: false,
+
+            last_chat: None,
         }
     }
 
     pub async fn run(&mut self) {
         while !self.disconnected {
             select! {
-                Some(packet) = self.packet_queue.recv() => {
-                    if let Err(err) = self.connection.write_packet(packet).await {
-                        error!("failed to write packet: {:#}", anyhow!(err));
+                Some(event) = self.event_queue.recv() => {
+                    if let Err(err) = self.process_event(event).await {
+                        error!("failed to process event: {:#}", anyhow!(err));
                     }
                 }
                 packet = self.connection.read_packet() => {
@@ -134,16 +208,35 @@ impl Client {
                         Err(ReceiveError::Decode(ReadError::UnrecognizedPacketId(id))) => {
                             debug!(
                                 "received unrecognized packet (state: {:?}, id: {:#04x})",
-                                self.connection.state, id
+                                self.connection.state(), id
                             );
                         },
                         Err(ReceiveError::ConnectionClosed) => self.disconnected = true,
+                        Err(ReceiveError::LegacyPing { host }) => {
+                            if let Err(err) = self.answer_legacy_ping(host.as_deref()).await {
+                                error!("failed to answer legacy ping: {:#}", anyhow!(err));
+                            }
+                            self.disconnected = true;
+                        }
                         Err(err) => {
                             error!("failed to read packet: {:#}", anyhow!(err));
                             let _ = self.disconnect("Bad packet.").await;
                         }
                     }
                 }
+                tick = self.ticks.recv() => {
+                    match tick {
+                        Ok(tick) => {
+                            if let Err(err) = self.tick(tick).await {
+                                error!("failed to process tick: {:#}", anyhow!(err));
+                            }
+                        }
+                        Err(RecvError::Lagged(skipped)) => {
+                            debug!("client fell behind by {} ticks", skipped);
+                        }
+                        Err(RecvError::Closed) => {}
+                    }
+                }
                 _ = self.shutdown.recv() => {
                     if let Err(err) = self.disconnect("Server is shutting down.").await {
                         error!("failed to disconnect client: {:#}", err);
@@ -155,10 +248,33 @@ impl Client {
         // This will fail if there are no recipients, but we don't care.
         let _ = self.stop.send(());
 
-        if let State::Play = self.connection.state {
+        if let Some(handle) = self.handle.take() {
             info!("client disconnected ({}, {})", self.name(), self.uuid());
 
-            ONLINE_PLAYERS.fetch_sub(1, Ordering::Relaxed);
+            self.state.players.remove(&handle);
+            self.state.status.update_players(&self.state.players);
+
+            // A newer connection for the same player keeps their place in the queue and the
+            // tab list.
+            if self.state.players.get(self.uuid()).is_none() {
+                self.state.queue.leave(self.uuid());
+
+                if self.state.config.read().await.tab_list.show_players {
+                    let uuid = *self.uuid();
+                    self.state
+                        .players
+                        .broadcast(|| ServerPacket::Play(tab_list::remove_player(uuid)));
+                }
+            }
+        }
+    }
+
+    async fn process_event(&mut self, event: ClientEvent) -> Result<(), SendError> {
+        match event {
+            ClientEvent::Packet(packet) => self.connection.write_packet(*packet).await,
+            ClientEvent::Disconnect(reason) => self.disconnect(reason).await,
+            ClientEvent::Transfer(server) => self.transfer(&server).await,
+            ClientEvent::KeepAlive => self.keep_alive().await,
         }
     }
 
@@ -168,39 +284,44 @@ impl Client {
                 ClientHandshakePacket::Handshake {
                     next_state,
                     protocol_version,
+                    server_address,
                     ..
                 } => {
-                    self.connection.state = next_state;
+                    self.connection.set_state(next_state);
+
+                    // Status pings are answered without touching the config, so the virtual
+                    // host is looked up in the status cache.
+                    self.vhost = self.state.status.find_vhost(&server_address);
 
                     if let State::Login = next_state {
+                        // The spawn point is only needed once the player logs in.
+                        if self.vhost.is_some() {
+                            let config = self.state.config.read().await;
+                            if let Some(vhost) = config.vhost(self.vhost) {
+                                debug!(
+                                    "{} connected through virtual host {}",
+                                    self.address,
+                                    vhost.hosts.join(", ")
+                                );
+                                self.spawn = vhost::spawn(vhost, &self.state.world.spawn);
+                            }
+                        }
+
                         if VERSION.protocol != protocol_version.0 as usize {
+                            metrics::login_failed("version_mismatch");
                             self.disconnect(&format!("Version mismatch between client and server. Please connect using {}.", VERSION.name)).await?;
                         }
-                    } else if self.config.read().await.info.hidden {
+                    } else if self.state.status.hidden(self.vhost) {
                         self.disconnect("").await?;
                     }
                 }
             },
             ClientPacket::Status(packet) => match packet {
                 ClientStatusPacket::Request {} => {
-                    let mut config = self.config.write().await;
-
-                    let player_info = if config.info.hide_player_count {
-                        None
-                    } else {
-                        Some(ServerPlayerInfo::simple(
-                            ONLINE_PLAYERS.load(Ordering::Relaxed) as isize,
-                            config.info.max_players,
-                        ))
-                    };
+                    metrics::status_request();
 
                     let response = ServerPacket::Status(ServerStatusPacket::Response {
-                        response: ServerInfo::new(
-                            VERSION,
-                            player_info,
-                            Message::new(config.info.motd.clone()),
-                            config.info.icon(),
-                        ),
+                        response: self.state.status.response(self.vhost),
                     });
 
                     self.connection.write_packet(response).await?;
@@ -213,6 +334,7 @@ impl Client {
             ClientPacket::Login(packet) => match packet {
                 ClientLoginPacket::Start { name } => {
                     if name.is_empty() || name.len() > 16 {
+                        metrics::login_failed("invalid_username");
                         self.disconnect("Usernames should be between 1-16 characters long.")
                             .await?;
                         return Ok(());
@@ -244,6 +366,7 @@ impl Client {
                             shared_secret
                         }
                         _ => {
+                            metrics::login_failed("invalid_verify_token");
                             self.disconnect("Invalid encryption challenge response.")
                                 .await?;
                             return Ok(());
@@ -251,24 +374,27 @@ impl Client {
                     };
 
                     if self.connection.update_encryption(&shared_secret).is_err() {
+                        metrics::login_failed("invalid_shared_secret");
                         self.disconnect("Unexpected shared secret key length.")
                             .await?;
                         return Ok(());
                     }
 
-                    let config_clone = self.config.clone();
-                    let config = config_clone.read().await;
-
                     self.set_compression(256).await?;
 
-                    let response = match mojang::authenticate(
+                    let timer = metrics::authentication_timer();
+                    let response = mojang::authenticate(
                         "",
                         &shared_secret,
                         &UNIVERSAL_ENCODED_RSA_PUBLIC_KEY,
                         self.name(),
-                    ) {
+                    );
+                    timer.observe_duration();
+
+                    let response = match response {
                         Ok(response) => response,
                         Err(err) => {
+                            metrics::login_failed("authentication");
                             error!("failed to authenticate {}: {:#}", self.name(), anyhow!(err));
                             self.disconnect("Could not validate session.").await?;
                             return Ok(());
@@ -278,98 +404,131 @@ impl Client {
                     let AuthenticationResponse { id, properties } = response;
                     self.uuid = Some(id);
 
+                    if let Some(ban) = self.state.bans.find(&id, self.name()) {
+                        metrics::login_failed("banned");
+                        self.disconnect(ban.reason).await?;
+                        return Ok(());
+                    }
+
                     self.connection
                         .write_packet(ServerPacket::Login(ServerLoginPacket::Success {
                             uuid: self.uuid.unwrap(),
                             name: self.name.clone().unwrap(),
                         }))
                         .await?;
-                    self.connection.state = State::Play;
+                    self.connection.set_state(State::Play);
+                    metrics::login_succeeded();
 
-                    info!("client logged in ({}, {})", self.name(), self.uuid());
+                    info!(
+                        "client logged in ({}, {}, {})",
+                        self.name(),
+                        self.uuid(),
+                        self.address
+                    );
 
-                    ONLINE_PLAYERS.fetch_add(1, Ordering::Relaxed);
+                    let mut handle = PlayerHandle::new(
+                        self.name().to_string(),
+                        *self.uuid(),
+                        self.address,
+                        self.event_send.clone(),
+                    );
+                    handle.properties = properties
+                        .into_iter()
+                        .map(|p| AddPlayerProperty {
+                            name: p.name,
+                            value: p.value,
+                            signature: BooleanPrefixedOption(p.signature),
+                        })
+                        .collect();
+                    if let Some(previous) = self.state.players.add(handle.clone()) {
+                        previous.send(ClientEvent::Disconnect(
+                            "You logged in from another location.".to_string(),
+                        ));
+                    }
+                    self.handle = Some(handle);
+                    self.state.status.update_players(&self.state.players);
 
                     self.start_keeping_alive();
 
-                    self.connection
-                        .write_packet(ServerPacket::Play(ServerPlayPacket::JoinGame {
-                            entity_id: 0,
-                            hardcore: true,
-                            gamemode: GameMode::Survival,
-                            previous_gamemode: None,
-                            world_names: VarIntPrefixedVec(vec!["limbo".to_string()]),
-                            dimension_codec: RawBytes::new(
-                                &include_bytes!("./dimension_codec.nbt")[..],
-                            ),
-                            dimension: RawBytes::new(&include_bytes!("./dimension.nbt")[..]),
-                            world_name: "limbo".to_string(),
-                            hashed_seed: 0,
-                            max_players: VarInt(1),
-                            view_distance: VarInt(32),
-                            simulation_distance: VarInt(32),
-                            reduced_debug_info: false,
-                            enable_respawn_screen: false,
-                            debug: false,
-                            flat: false,
-                        }))
-                        .await?;
+                    // Everything that depends on the config is built before writing any of it, so
+                    // a client that's slow to read doesn't hold the config lock and block reloads.
+                    let (join, spawn) = {
+                        let state = self.state.clone();
+                        let config = state.config.read().await;
+                        let packets = (self.join_packets(&config)?, self.spawn_packets(&config));
 
-                    self.send_plugin_message("minecraft:brand", &config.info.name)
-                        .await?;
+                        if config.tab_list.show_players {
+                            // The joining player is already part of the list they're sent.
+                            let handle = self.handle.clone().unwrap();
+                            let game_mode = config.player.game_mode;
+                            self.state.players.broadcast_except(&handle.uuid, || {
+                                ServerPacket::Play(tab_list::add_players(
+                                    std::slice::from_ref(&handle),
+                                    game_mode,
+                                ))
+                            });
+                        }
 
-                    self.connection
-                        .write_packet(ServerPacket::Play(ServerPlayPacket::SpawnPosition {
-                            angle: 0.0,
-                            location: Position::new(0, 64, 0),
-                        }))
-                        .await?;
+                        // The welcome is built before joining, so it doesn't show a queue
+                        // position yet.
+                        if config.queue.enabled {
+                            let priority = queue::priority(&config.queue, self.uuid(), self.name());
+                            if self.state.queue.join(
+                                *self.uuid(),
+                                self.name().to_string(),
+                                priority,
+                            ) {
+                                info!("{} got their place in the queue back", self.name());
+                            } else {
+                                debug!("{} joined the queue (priority {})", self.name(), priority);
+                            }
+                        }
 
-                    // TODO: Abstract this away into a proper teleport function.
-                    self.connection
-                        .write_packet(ServerPacket::Play(
-                            ServerPlayPacket::PlayerPositionAndLook {
-                                x: 0.0,
-                                y: 64.0,
-                                z: 0.0,
-                                yaw: 0.0,
-                                pitch: 0.0,
-                                flags: 0,
-                                teleport_id: VarInt(0),
-                                dismount_vehicle: true,
-                            },
-                        ))
-                        .await?;
+                        packets
+                    };
 
-                    // TODO: Abstract this away in some kind of TAB-screen handler.
-                    self.connection
-                        .write_packet(ServerPacket::Play(ServerPlayPacket::PlayerInfo {
-                            info: PlayerInfo::AddPlayer(VarIntPrefixedVec(vec![AddPlayerAction {
-                                uuid: *self.uuid(),
-                                name: self.name().to_string(),
-                                properties: VarIntPrefixedVec(
-                                    properties
-                                        .into_iter()
-                                        .map(|p| AddPlayerProperty {
-                                            name: p.name,
-                                            value: p.value,
-                                            signature: BooleanPrefixedOption(p.signature),
-                                        })
-                                        .collect(),
-                                ),
-                                game_mode: GameMode::Survival,
-                                ping: VarInt(0), // TODO: Appropriately set this.
-                                display_name: BooleanPrefixedOption(None),
-                            }])),
-                        }))
-                        .await?;
+                    for packet in join {
+                        self.connection
+                            .write_packet(ServerPacket::Play(packet))
+                            .await?;
+                    }
+
+                    for packet in self.state.world.chunk_packets() {
+                        self.connection
+                            .write_packet(ServerPacket::Play(packet))
+                            .await?;
+                    }
+
+                    self.teleport(self.spawn.location()).await?;
+
+                    for packet in spawn {
+                        self.connection
+                            .write_packet(ServerPacket::Play(packet))
+                            .await?;
+                    }
                 }
             },
             ClientPacket::Play(packet) => match packet {
-                ClientPlayPacket::TeleportConfirm { .. } => {}
+                ClientPlayPacket::TeleportConfirm { id } => {
+                    if self.pending_teleport == Some(id.0) {
+                        self.pending_teleport = None;
+                    } else {
+                        debug!(
+                            "{} confirmed unexpected teleport (id: {}, expected: {:?})",
+                            self.name(),
+                            id,
+                            self.pending_teleport
+                        );
+                    }
+                }
                 ClientPlayPacket::PluginMessage { channel, data } => match channel.as_str() {
                     "minecraft:brand" => match String::read_from(&mut &data.0[..]) {
-                        Ok(brand) => debug!("client brand of {} is {}", self.name(), brand),
+                        Ok(brand) => {
+                            debug!("client brand of {} is {}", self.name(), brand);
+                            if let Some(handle) = &self.handle {
+                                handle.set_brand(brand);
+                            }
+                        }
                         Err(err) => warn!(
                             "failed to process client brand of {}: {:#}",
                             self.name(),
@@ -382,7 +541,15 @@ impl Client {
                         self.name()
                     ),
                 },
-                ClientPlayPacket::PlayerPosition { .. } => {}
+                ClientPlayPacket::PlayerPosition { x, y, z, .. } => {
+                    let location = Location {
+                        x,
+                        y,
+                        z,
+                        ..self.location
+                    };
+                    self.move_to(location).await?;
+                }
                 ClientPlayPacket::ClientSettings {
                     displayed_skin_parts,
                     main_hand,
@@ -398,16 +565,368 @@ impl Client {
                         }))
                         .await?;
                 }
-                ClientPlayPacket::KeepAlive { .. } => {
-                    // TODO: Check if the ID matches.
+                ClientPlayPacket::KeepAlive { id } => {
+                    // Only answers to the last keep-alive count, so clients can't make up their
+                    // own latency.
+                    let ping = match self.pending_keep_alive {
+                        Some((pending, sent_at)) if pending == id => {
+                            self.pending_keep_alive = None;
+                            sent_at.elapsed().as_millis() as u32
+                        }
+                        _ => {
+                            debug!("{} answered an unknown keep-alive ({})", self.name(), id);
+                            return Ok(());
+                        }
+                    };
+
+                    metrics::keep_alive(ping);
+
+                    if let Some(handle) = self.handle.clone() {
+                        handle.set_ping(ping);
+
+                        if self.state.config.read().await.tab_list.show_players {
+                            self.state.players.broadcast(|| {
+                                ServerPacket::Play(tab_list::update_latency(&handle))
+                            });
+                        } else {
+                            self.connection
+                                .write_packet(ServerPacket::Play(tab_list::update_latency(&handle)))
+                                .await?;
+                        }
+                    }
+                }
+                ClientPlayPacket::PlayerPositionAndRotation {
+                    x,
+                    y,
+                    z,
+                    yaw,
+                    pitch,
+                    ..
+                } => {
+                    self.move_to(Location {
+                        x,
+                        y,
+                        z,
+                        yaw,
+                        pitch,
+                    })
+                    .await?;
+                }
+                ClientPlayPacket::PlayerRotation { yaw, pitch, .. } => {
+                    let location = Location {
+                        yaw,
+                        pitch,
+                        ..self.location
+                    };
+                    self.move_to(location).await?;
                 }
-                ClientPlayPacket::PlayerPositionAndRotation { .. } => {}
+                ClientPlayPacket::ChatMessage { message } => {
+                    self.mark_active();
+
+                    match message.strip_prefix('/') {
+                        Some(command) => self.run_command(command).await?,
+                        None => self.chat(message).await?,
+                    }
+                }
+                ClientPlayPacket::TabComplete { id, text } => {
+                    self.tab_complete(id, &text).await?;
+                }
+                ClientPlayPacket::PlayerMovement { .. } => {}
+                ClientPlayPacket::PlayerAbilities { .. } => self.mark_active(),
+                ClientPlayPacket::Animation { .. } => self.mark_active(),
             },
         }
 
         Ok(())
     }
 
+    /// Processes movement reported by the client, sending it back to spawn if it left the
+    /// configured boundaries.
+    async fn move_to(&mut self, location: Location) -> Result<(), SendError> {
+        // Until the client confirms the last teleport, it may still report its old position.
+        if self.pending_teleport.is_some() {
+            return Ok(());
+        }
+
+        // Clients resend their position every second, even when standing still. Falling doesn't
+        // count either, since idle players in a void world keep falling and being teleported
+        // back to spawn.
+        let walked = location.x != self.location.x || location.z != self.location.z;
+        let turned = location.yaw != self.location.yaw || location.pitch != self.location.pitch;
+        if walked || turned {
+            self.mark_active();
+        }
+
+        let (frozen, out_of_bounds) = {
+            let config = self.state.config.read().await;
+            let spawn = self.spawn.location();
+
+            let moved = location.x != self.location.x
+                || location.y != self.location.y
+                || location.z != self.location.z;
+
+            let below = matches!(config.boundaries.min_y, Some(min_y) if location.y < min_y);
+            let outside = matches!(
+                config.boundaries.radius,
+                Some(radius) if location.horizontal_distance(&spawn) > radius
+            );
+
+            (config.player.frozen && moved, below || outside)
+        };
+
+        if frozen {
+            // Frozen players can still look around, they just stay in place.
+            let location = Location {
+                yaw: location.yaw,
+                pitch: location.pitch,
+                ..self.location
+            };
+            self.teleport(location).await?;
+            return Ok(());
+        }
+
+        self.location = location;
+
+        if out_of_bounds {
+            debug!("{} left the boundaries, teleporting to spawn", self.name());
+            self.teleport(self.spawn.location()).await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn teleport(&mut self, location: Location) -> Result<(), SendError> {
+        let teleport_id = self.next_teleport_id;
+        self.next_teleport_id = self.next_teleport_id.wrapping_add(1);
+
+        self.connection
+            .write_packet(ServerPacket::Play(
+                ServerPlayPacket::PlayerPositionAndLook {
+                    x: location.x,
+                    y: location.y,
+                    z: location.z,
+                    yaw: location.yaw,
+                    pitch: location.pitch,
+                    flags: 0,
+                    teleport_id: VarInt(teleport_id),
+                    dismount_vehicle: true,
+                },
+            ))
+            .await?;
+
+        self.location = location;
+        self.pending_teleport = Some(teleport_id);
+
+        Ok(())
+    }
+
+    async fn tick(&mut self, tick: Tick) -> Result<(), SendError> {
+        if !matches!(self.connection.state(), State::Play) {
+            return Ok(());
+        }
+
+        if tick.every(TICKS_PER_SECOND) {
+            let (locked_time, weather) = {
+                let config = self.state.config.read().await;
+                self.tab_list_interval = config.tab_list.refresh_interval.max(1);
+                self.sidebar_interval = config.sidebar.refresh_interval.max(1);
+                (config.world.locked_time, config.world.weather)
+            };
+
+            self.connection
+                .write_packet(ServerPacket::Play(tick.time_update(locked_time)))
+                .await?;
+
+            if self.weather != Some(weather) {
+                self.send_weather(weather).await?;
+            }
+
+            self.update_queue_position().await?;
+            self.check_idle().await?;
+        }
+
+        let refresh_tab_list = tick.every(self.tab_list_interval);
+        let refresh_sidebar = tick.every(self.sidebar_interval);
+        if !refresh_tab_list && !refresh_sidebar {
+            return Ok(());
+        }
+
+        // The packets are built before writing any of them, so a client that's slow to read
+        // doesn't hold the config lock and block reloads.
+        let mut packets = Vec::new();
+        {
+            let state = self.state.clone();
+            let config = state.config.read().await;
+
+            if refresh_tab_list {
+                packets.extend(self.header_and_footer(&config));
+            }
+
+            if refresh_sidebar {
+                packets.extend(self.sidebar_packets(&config));
+            }
+        }
+
+        for packet in packets {
+            self.connection
+                .write_packet(ServerPacket::Play(packet))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn mark_active(&mut self) {
+        self.last_activity = Instant::now();
+        self.idle_warned = false;
+    }
+
+    /// Warns players that have been idle for a while, and kicks or transfers them once they hit
+    /// the configured timeout.
+    async fn check_idle(&mut self) -> Result<(), SendError> {
+        let config = self.state.config.read().await.idle.clone();
+        if !config.enabled || players::is_listed(&config.bypass, self.uuid(), self.name()) {
+            return Ok(());
+        }
+
+        let idle = self.last_activity.elapsed();
+        let timeout = Duration::from_secs(config.timeout);
+        let warning = timeout.saturating_sub(Duration::from_secs(config.warning));
+
+        if idle >= timeout {
+            // Don't try again right away if the transfer fails.
+            self.mark_active();
+
+            match config.transfer_server {
+                Some(server) => {
+                    info!("{} has been idle for too long", self.name());
+                    self.transfer(&server).await?;
+                }
+                None => {
+                    info!("kicking {} for being idle", self.name());
+                    self.disconnect(config.kick_message).await?;
+                }
+            }
+        } else if idle >= warning && !self.idle_warned {
+            self.idle_warned = true;
+
+            let seconds = (timeout - idle).as_secs().to_string();
+            let format =
+                |text: Option<String>| text.map(|text| text.replace("{seconds}", &seconds));
+
+            if let Some(message) = format(config.warning_message) {
+                self.send_message(message, ChatPosition::System).await?;
+            }
+
+            self.send_title(
+                format(config.warning_title),
+                format(config.warning_subtitle),
+            )
+            .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn update_queue_position(&mut self) -> Result<(), SendError> {
+        let config = self.state.config.read().await;
+        if !config.queue.enabled {
+            return Ok(());
+        }
+
+        let (position, size) = match self.state.queue.position(self.uuid()) {
+            Some(position) => position,
+            None => {
+                if self.queue_bar {
+                    self.queue_bar = false;
+                    self.connection
+                        .write_packet(ServerPacket::Play(ServerPlayPacket::BossBar {
+                            uuid: QUEUE_BAR,
+                            action: BossBarAction::Remove,
+                        }))
+                        .await?;
+                }
+
+                return Ok(());
+            }
+        };
+
+        let format = |text: &str| {
+            text.replace("{position}", &position.to_string())
+                .replace("{size}", &size.to_string())
+        };
+
+        if let Some(ref text) = config.queue.action_bar {
+            self.connection
+                .write_packet(ServerPacket::Play(ServerPlayPacket::ActionBar {
+                    text: Message::new(format(text)),
+                }))
+                .await?;
+        }
+
+        if let Some(ref text) = config.queue.boss_bar {
+            let title = Message::new(format(text));
+            let health = 1.0 - (position - 1) as f32 / size as f32;
+
+            let actions = if self.queue_bar {
+                vec![
+                    BossBarAction::UpdateTitle(title),
+                    BossBarAction::UpdateHealth(health),
+                ]
+            } else {
+                vec![BossBarAction::Add {
+                    title,
+                    health,
+                    color: config.queue.boss_bar_color,
+                    division: BossBarDivision::Progress,
+                    flags: 0,
+                }]
+            };
+
+            for action in actions {
+                self.connection
+                    .write_packet(ServerPacket::Play(ServerPlayPacket::BossBar {
+                        uuid: QUEUE_BAR,
+                        action,
+                    }))
+                    .await?;
+            }
+
+            self.queue_bar = true;
+        }
+
+        Ok(())
+    }
+
+    async fn send_weather(&mut self, weather: Weather) -> Result<(), SendError> {
+        for packet in self.weather_packets(weather) {
+            self.connection
+                .write_packet(ServerPacket::Play(packet))
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    fn weather_packets(&mut self, weather: Weather) -> Vec<ServerPlayPacket> {
+        let (reason, rain, thunder) = match weather {
+            Weather::Clear => (GameStateChange::EndRaining, 0.0, 0.0),
+            Weather::Rain => (GameStateChange::BeginRaining, 1.0, 0.0),
+            Weather::Thunder => (GameStateChange::BeginRaining, 1.0, 1.0),
+        };
+
+        self.weather = Some(weather);
+
+        [
+            (reason, 0.0),
+            (GameStateChange::RainLevelChange, rain),
+            (GameStateChange::ThunderLevelChange, thunder),
+        ]
+        .into_iter()
+        .map(|(reason, value)| ServerPlayPacket::ChangeGameState { reason, value })
+        .collect()
+    }
+
     async fn set_compression(&mut self, threshold: usize) -> Result<(), SendError> {
         self.connection
             .write_packet(ServerPacket::Login(ServerLoginPacket::SetCompression {
@@ -421,7 +940,7 @@ impl Client {
     }
 
     fn start_keeping_alive(&mut self) {
-        let send_queue = self.packet_send.clone();
+        let send_queue = self.event_send.clone();
         let mut stop = self.stop.subscribe();
         tokio::spawn(async move {
             let mut interval = time::interval(KEEP_ALIVE_INTERVAL);
@@ -429,10 +948,7 @@ impl Client {
             loop {
                 select! {
                     _ = interval.tick() => {
-                        // TODO: Properly process the client response to this.
-                        send_queue.send(ServerPacket::Play(ServerPlayPacket::KeepAlive { id: 0 }))
-                            .await
-                            .unwrap();
+                        send_queue.send(ClientEvent::KeepAlive).await.unwrap();
                     }
                     _ = stop.recv() => break
                 }
@@ -442,16 +958,441 @@ impl Client {
         });
     }
 
+    /// Sends a keep-alive, replacing the previous one if it hasn't been answered yet.
+    async fn keep_alive(&mut self) -> Result<(), SendError> {
+        let id = unix_millis();
+        self.pending_keep_alive = Some((id, Instant::now()));
+
+        self.connection
+            .write_packet(ServerPacket::Play(ServerPlayPacket::KeepAlive { id }))
+            .await
+    }
+
+    async fn transfer(&mut self, server: &str) -> Result<(), SendError> {
+        info!("transferring {} to {}", self.name(), server);
+
+        self.send_plugin_message(
+            PROXY_CHANNEL,
+            &ProxyMessage::Connect {
+                server: server.to_string(),
+            },
+        )
+        .await
+    }
+
+    /// Builds the packets sent when a player joins, before the world.
+    fn join_packets(&self, config: &Config) -> Result<Vec<ServerPlayPacket>, SendError> {
+        Ok(vec![
+            ServerPlayPacket::JoinGame {
+                entity_id: 0,
+                hardcore: true,
+                gamemode: config.player.game_mode,
+                previous_gamemode: None,
+                world_names: VarIntPrefixedVec(vec!["limbo".to_string()]),
+                dimension_codec: RawBytes::new(&include_bytes!("./dimension_codec.nbt")[..]),
+                dimension: RawBytes::new(&include_bytes!("./dimension.nbt")[..]),
+                world_name: "limbo".to_string(),
+                hashed_seed: 0,
+                max_players: VarInt(1),
+                view_distance: VarInt(32),
+                simulation_distance: VarInt(32),
+                reduced_debug_info: false,
+                enable_respawn_screen: false,
+                debug: false,
+                flat: false,
+            },
+            plugin_message("minecraft:brand", &config.info.name)?,
+            ServerPlayPacket::PlayerAbilities {
+                abilities: Abilities {
+                    invulnerable: config.player.invulnerable,
+                    flying: config.player.flying,
+                    allow_flying: config.player.allow_flying,
+                    instant_break: config.player.game_mode == GameMode::Creative,
+                },
+                // Frozen players are held in place by teleporting them back in `move_to`. A
+                // flying speed of zero keeps them from flying off in between, but the walking
+                // speed only changes the client's FOV, so it's left alone.
+                flying_speed: if config.player.frozen {
+                    0.0
+                } else {
+                    config.player.flying_speed
+                },
+                walking_speed: config.player.walking_speed,
+            },
+            ServerPlayPacket::SpawnPosition {
+                angle: self.spawn.angle,
+                location: self.spawn.position.clone(),
+            },
+            ServerPlayPacket::UpdateViewPosition {
+                chunk_x: VarInt(self.spawn.position.x.div_euclid(16)),
+                chunk_z: VarInt(self.spawn.position.z.div_euclid(16)),
+            },
+        ])
+    }
+
+    /// Builds the packets sent once a player has been placed in the world.
+    fn spawn_packets(&mut self, config: &Config) -> Vec<ServerPlayPacket> {
+        let mut packets = Vec::new();
+
+        if let (true, Some(radius)) = (config.boundaries.world_border, config.boundaries.radius) {
+            let center = self.spawn.location();
+            packets.push(ServerPlayPacket::InitializeWorldBorder {
+                x: center.x,
+                z: center.z,
+                old_diameter: radius * 2.0,
+                new_diameter: radius * 2.0,
+                speed: VarLong(0),
+                portal_teleport_boundary: VarInt(MAX_WORLD_BORDER),
+                warning_blocks: VarInt(0),
+                warning_time: VarInt(0),
+            });
+        }
+
+        packets.push(Tick::current().time_update(config.world.locked_time));
+        packets.extend(self.weather_packets(config.world.weather));
+
+        let handle = self.handle.clone().unwrap();
+        let players = if config.tab_list.show_players {
+            self.state.players.all()
+        } else {
+            vec![handle]
+        };
+        packets.push(tab_list::add_players(&players, config.player.game_mode));
+
+        packets.extend(self.header_and_footer(config));
+        packets.push(commands_packet(config));
+        packets.extend(self.welcome_packets(config));
+
+        if config.transfer.enabled && !self.state.backend_online.load(Ordering::Relaxed) {
+            if let Some(ref message) = config.transfer.waiting_message {
+                packets.push(chat_message(message.clone(), ChatPosition::System));
+            }
+
+            packets.extend(title_packets(
+                config.transfer.waiting_title.clone(),
+                config.transfer.waiting_subtitle.clone(),
+            ));
+        }
+
+        packets
+    }
+
+    fn welcome_packets(&self, config: &Config) -> Vec<ServerPlayPacket> {
+        let welcome = config
+            .vhost(self.vhost)
+            .and_then(|vhost| vhost.welcome.as_ref())
+            .unwrap_or(&config.welcome);
+
+        let mut packets = welcome
+            .messages
+            .iter()
+            .map(|message| chat_message(self.format(message, config), ChatPosition::System))
+            .collect::<Vec<_>>();
+
+        if welcome.title.is_some() || welcome.subtitle.is_some() {
+            packets.push(ServerPlayPacket::SetTitleTimes {
+                fade_in: welcome.fade_in,
+                stay: welcome.stay,
+                fade_out: welcome.fade_out,
+            });
+
+            packets.extend(title_packets(
+                welcome
+                    .title
+                    .as_ref()
+                    .map(|title| self.format(title, config)),
+                welcome
+                    .subtitle
+                    .as_ref()
+                    .map(|subtitle| self.format(subtitle, config)),
+            ));
+        }
+
+        if let Some(ref text) = welcome.action_bar {
+            packets.push(ServerPlayPacket::ActionBar {
+                text: Message::new(self.format(text, config)),
+            });
+        }
+
+        if let Some(ref title) = welcome.boss_bar {
+            packets.push(ServerPlayPacket::BossBar {
+                uuid: WELCOME_BAR,
+                action: BossBarAction::Add {
+                    title: Message::new(self.format(title, config)),
+                    health: welcome.boss_bar_progress.clamp(0.0, 1.0),
+                    color: welcome.boss_bar_color,
+                    division: welcome.boss_bar_division,
+                    flags: 0,
+                },
+            });
+        }
+
+        packets
+    }
+
+    fn header_and_footer(&self, config: &Config) -> Option<ServerPlayPacket> {
+        let tab_list = &config.tab_list;
+        if tab_list.header.is_none() && tab_list.footer.is_none() {
+            return None;
+        }
+
+        Some(tab_list::header_and_footer(
+            tab_list
+                .header
+                .as_ref()
+                .map(|header| self.format(header, config)),
+            tab_list
+                .footer
+                .as_ref()
+                .map(|footer| self.format(footer, config)),
+        ))
+    }
+
+    /// Builds the packets that bring the client's sidebar up to date.
+    fn sidebar_packets(&mut self, config: &Config) -> Vec<ServerPlayPacket> {
+        match config.sidebar.title {
+            Some(ref title) => {
+                let title = self.format(title, config);
+                let lines = config
+                    .sidebar
+                    .lines
+                    .iter()
+                    .map(|line| self.format(line, config))
+                    .collect();
+                self.sidebar.update(title, lines)
+            }
+            None => self.sidebar.clear(),
+        }
+    }
+
+    /// Fills in the placeholders that can be used in any text sent to the player.
+    fn format(&self, text: &str, config: &Config) -> String {
+        let max = match config.info.max_players {
+            max if max < 0 => "∞".to_string(),
+            max => max.to_string(),
+        };
+
+        let (queue_position, queue_size) = match self.state.queue.position(self.uuid()) {
+            Some((position, size)) => (position.to_string(), size.to_string()),
+            None => ("-".to_string(), "-".to_string()),
+        };
+
+        let waited = self
+            .handle
+            .as_ref()
+            .and_then(|handle| handle.joined_at.elapsed().ok())
+            .unwrap_or_default()
+            .as_secs();
+
+        text.replace("{player}", self.name())
+            .replace("{online}", &self.state.players.len().to_string())
+            .replace("{max}", &max)
+            .replace("{server}", &config.info.name)
+            .replace("{queue_position}", &queue_position)
+            .replace("{queue_size}", &queue_size)
+            .replace("{waited}", &format!("{}:{:02}", waited / 60, waited % 60))
+    }
+
+    async fn run_command(&mut self, input: &str) -> Result<(), SendError> {
+        let state = self.state.clone();
+        let config = state.config.read().await;
+        let registry = CommandRegistry::new(&config.commands);
+
+        let (name, args) = commands::parse(input);
+        let kind = match registry.find(name) {
+            Some(command) => command.kind.clone(),
+            None => {
+                debug!("{} issued unknown command /{}", self.name(), input);
+                return self
+                    .send_message(
+                        config.commands.unknown_message.clone(),
+                        ChatPosition::System,
+                    )
+                    .await;
+            }
+        };
+
+        info!("{} issued command /{}", self.name(), input);
+
+        match kind {
+            CommandKind::Help => {
+                let lines = registry
+                    .all()
+                    .iter()
+                    .map(|command| format!("§6/{}§r: {}", command.name, command.description))
+                    .collect::<Vec<_>>();
+
+                self.send_message(lines.join("\n"), ChatPosition::System)
+                    .await
+            }
+            CommandKind::Spawn => self.teleport(self.spawn.location()).await,
+            CommandKind::Ping => {
+                let ping = self.handle.as_ref().map_or(0, PlayerHandle::ping);
+                self.send_message(format!("Your ping is {} ms.", ping), ChatPosition::System)
+                    .await
+            }
+            CommandKind::Hub { server } => self.transfer(&server).await,
+            CommandKind::Server => match args.first() {
+                Some(server) if config.commands.servers.iter().any(|s| s == server) => {
+                    self.transfer(server).await
+                }
+                Some(server) => {
+                    self.send_message(format!("Unknown server {}.", server), ChatPosition::System)
+                        .await
+                }
+                None => {
+                    let servers = config.commands.servers.join(", ");
+                    self.send_message(
+                        format!("You can connect to: {}", servers),
+                        ChatPosition::System,
+                    )
+                    .await
+                }
+            },
+        }
+    }
+
+    async fn tab_complete(&mut self, id: VarInt, text: &str) -> Result<(), SendError> {
+        let state = self.state.clone();
+        let config = state.config.read().await;
+        let registry = CommandRegistry::new(&config.commands);
+
+        // Only the argument of /server asks the server for suggestions.
+        let (name, prefix) = match text.strip_prefix('/').and_then(|text| text.split_once(' ')) {
+            Some((name, prefix)) if !prefix.contains(' ') => (name, prefix),
+            _ => return Ok(()),
+        };
+
+        if !matches!(registry.find(name), Some(command) if command.kind == CommandKind::Server) {
+            return Ok(());
+        }
+
+        let matches = config
+            .commands
+            .servers
+            .iter()
+            .filter(|server| server.starts_with(prefix))
+            .map(|server| TabCompleteMatch {
+                text: server.clone(),
+                tooltip: BooleanPrefixedOption(None),
+            })
+            .collect();
+
+        // The client counts these in UTF-16 code units, like Java strings.
+        let length = prefix.encode_utf16().count();
+        self.connection
+            .write_packet(ServerPacket::Play(ServerPlayPacket::TabComplete {
+                id,
+                start: VarInt((text.encode_utf16().count() - length) as i32),
+                length: VarInt(length as i32),
+                matches: VarIntPrefixedVec(matches),
+            }))
+            .await
+    }
+
+    async fn chat(&mut self, message: String) -> Result<(), SendError> {
+        let state = self.state.clone();
+        let config = state.config.read().await;
+        let chat = &config.chat;
+
+        // Rejected messages get an optional reply explaining why.
+        let rejection = if chat.mode == ChatMode::Disabled {
+            Some(&chat.disabled_message)
+        } else if players::is_listed(&chat.muted, self.uuid(), self.name()) {
+            Some(&chat.muted_message)
+        } else if matches!(self.last_chat, Some(last) if last.elapsed().as_secs_f64() < chat.cooldown)
+        {
+            Some(&chat.cooldown_message)
+        } else {
+            None
+        };
+
+        if let Some(reply) = rejection {
+            if let Some(reply) = reply {
+                self.send_message(reply.clone(), ChatPosition::System)
+                    .await?;
+            }
+
+            return Ok(());
+        }
+
+        self.last_chat = Some(Instant::now());
+
+        // Players shouldn't be able to use formatting codes, even with a modified client.
+        let mut message = message.replace('§', "");
+        if message.chars().count() > chat.max_length {
+            message = message.chars().take(chat.max_length).collect();
+        }
+
+        if message.trim().is_empty() {
+            return Ok(());
+        }
+
+        if chat.log {
+            info!("<{}> {}", self.name(), message);
+        }
+
+        // The message is inserted last, so players can't use placeholders themselves.
+        let text = self
+            .format(&chat.format, &config)
+            .replace("{message}", &message);
+
+        match chat.mode {
+            ChatMode::Global => {
+                let sender = *self.uuid();
+                self.state.players.broadcast(|| {
+                    ServerPacket::Play(ServerPlayPacket::ChatMessage {
+                        message: Message::new(text.clone()),
+                        position: ChatPosition::Chat,
+                        sender,
+                    })
+                });
+            }
+            _ => {
+                self.connection
+                    .write_packet(ServerPacket::Play(ServerPlayPacket::ChatMessage {
+                        message: Message::new(text),
+                        position: ChatPosition::Chat,
+                        sender: *self.uuid(),
+                    }))
+                    .await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn send_message<S: Into<String>>(
+        &mut self,
+        message: S,
+        position: ChatPosition,
+    ) -> Result<(), SendError> {
+        self.connection
+            .write_packet(ServerPacket::Play(chat_message(message, position)))
+            .await
+    }
+
+    async fn send_title(
+        &mut self,
+        title: Option<String>,
+        subtitle: Option<String>,
+    ) -> Result<(), SendError> {
+        for packet in title_packets(title, subtitle) {
+            self.connection
+                .write_packet(ServerPacket::Play(packet))
+                .await?;
+        }
+
+        Ok(())
+    }
+
     async fn send_plugin_message<S: Display + ToString, D: PacketField>(
         &mut self,
         channel: S,
         data: &D,
     ) -> Result<(), SendError> {
         self.connection
-            .write_packet(ServerPacket::Play(ServerPlayPacket::PluginMessage {
-                channel: channel.to_string(),
-                data: RawBytes::new(data.write_to_vec()?),
-            }))
+            .write_packet(ServerPacket::Play(plugin_message(&channel, data)?))
             .await?;
 
         debug!(
@@ -463,12 +1404,24 @@ impl Client {
         Ok(())
     }
 
+    /// Answers a server list ping from a client older than 1.7, with the hostname it sent if
+    /// it's new enough to send one.
+    async fn answer_legacy_ping(&mut self, host: Option<&str>) -> Result<(), SendError> {
+        metrics::status_request();
+
+        let vhost = host.and_then(|host| self.state.status.find_vhost(host));
+        match self.state.status.legacy_response(vhost) {
+            Some(response) => self.connection.write_legacy_kick(&response).await,
+            None => Ok(()),
+        }
+    }
+
     async fn disconnect<S: Display + ToString>(&mut self, reason: S) -> Result<(), SendError> {
         // TODO: Actually disconnect when this function is called, instead of after the next packet.
 
         self.disconnected = true;
 
-        match self.connection.state {
+        match self.connection.state() {
             State::Login => {
                 let disconnect = ServerPacket::Login(ServerLoginPacket::Disconnect {
                     reason: Message::new(reason.to_string()),
@@ -505,3 +1458,56 @@ impl Client {
         self.uuid.as_ref().unwrap()
     }
 }
+
+fn chat_message<S: Into<String>>(message: S, position: ChatPosition) -> ServerPlayPacket {
+    ServerPlayPacket::ChatMessage {
+        message: Message::new(message.into()),
+        position,
+        sender: Uuid::nil(),
+    }
+}
+
+fn title_packets(title: Option<String>, subtitle: Option<String>) -> Vec<ServerPlayPacket> {
+    if title.is_none() && subtitle.is_none() {
+        return Vec::new();
+    }
+
+    // The title is only shown once its text is set, so the subtitle has to come first.
+    let mut packets = Vec::new();
+    if let Some(subtitle) = subtitle {
+        packets.push(ServerPlayPacket::SetTitleSubtitle {
+            text: Message::new(subtitle),
+        });
+    }
+
+    packets.push(ServerPlayPacket::SetTitleText {
+        text: Message::new(title.unwrap_or_default()),
+    });
+    packets
+}
+
+fn plugin_message<S: ToString, D: PacketField>(
+    channel: S,
+    data: &D,
+) -> Result<ServerPlayPacket, SendError> {
+    Ok(ServerPlayPacket::PluginMessage {
+        channel: channel.to_string(),
+        data: RawBytes::new(data.write_to_vec()?),
+    })
+}
+
+fn commands_packet(config: &Config) -> ServerPlayPacket {
+    let (nodes, root_index) = command::graph(&CommandRegistry::new(&config.commands).tree());
+
+    ServerPlayPacket::DeclareCommands {
+        nodes: VarIntPrefixedVec(nodes),
+        root_index,
+    }
+}
+
+fn unix_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
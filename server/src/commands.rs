@@ -0,0 +1,113 @@
+use protocol::command::{Command, Parser, StringKind, ASK_SERVER};
+
+use crate::config;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CommandKind {
+    Help,
+    Spawn,
+    Ping,
+    Hub { server: String },
+    Server,
+}
+
+pub struct RegisteredCommand {
+    pub name: String,
+    pub aliases: Vec<String>,
+    pub description: &'static str,
+    pub kind: CommandKind,
+}
+
+/// The commands players can use, which depend on the configuration.
+pub struct CommandRegistry {
+    commands: Vec<RegisteredCommand>,
+}
+
+impl CommandRegistry {
+    pub fn new(config: &config::Commands) -> CommandRegistry {
+        let mut commands = vec![
+            RegisteredCommand {
+                name: "help".to_string(),
+                aliases: Vec::new(),
+                description: "Shows this list of commands.",
+                kind: CommandKind::Help,
+            },
+            RegisteredCommand {
+                name: "spawn".to_string(),
+                aliases: Vec::new(),
+                description: "Teleports you back to spawn.",
+                kind: CommandKind::Spawn,
+            },
+            RegisteredCommand {
+                name: "ping".to_string(),
+                aliases: Vec::new(),
+                description: "Shows your latency to the server.",
+                kind: CommandKind::Ping,
+            },
+        ];
+
+        if let (Some(server), Some((name, aliases))) =
+            (&config.hub_server, config.hub_aliases.split_first())
+        {
+            commands.push(RegisteredCommand {
+                name: name.clone(),
+                aliases: aliases.to_vec(),
+                description: "Sends you back to the hub.",
+                kind: CommandKind::Hub {
+                    server: server.clone(),
+                },
+            });
+        }
+
+        if !config.servers.is_empty() {
+            commands.push(RegisteredCommand {
+                name: "server".to_string(),
+                aliases: Vec::new(),
+                description: "Sends you to another server.",
+                kind: CommandKind::Server,
+            });
+        }
+
+        CommandRegistry { commands }
+    }
+
+    pub fn all(&self) -> &[RegisteredCommand] {
+        &self.commands
+    }
+
+    pub fn find(&self, name: &str) -> Option<&RegisteredCommand> {
+        self.commands.iter().find(|command| {
+            command.name.eq_ignore_ascii_case(name)
+                || command
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.eq_ignore_ascii_case(name))
+        })
+    }
+
+    /// Builds the command tree sent to the client, which it uses for highlighting and suggestions.
+    /// Aliases get their own copy of the command, like separately registered commands.
+    pub fn tree(&self) -> Vec<Command> {
+        self.commands
+            .iter()
+            .flat_map(|command| {
+                std::iter::once(&command.name).chain(&command.aliases).map(
+                    move |name| match command.kind {
+                        CommandKind::Server => Command::literal(name).executes().then(
+                            Command::argument("server", Parser::String(StringKind::SingleWord))
+                                .suggests(ASK_SERVER)
+                                .executes(),
+                        ),
+                        _ => Command::literal(name).executes(),
+                    },
+                )
+            })
+            .collect()
+    }
+}
+
+/// Splits a command, without the leading slash, into its name and arguments.
+pub fn parse(input: &str) -> (&str, Vec<&str>) {
+    let mut parts = input.split_whitespace();
+    (parts.next().unwrap_or_default(), parts.collect())
+}
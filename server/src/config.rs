@@ -1,9 +1,19 @@
-use std::{fs::OpenOptions, io::Write, path::Path};
+use std::{collections::HashSet, fs::OpenOptions, io::Write, path::Path};
 
-use anyhow::anyhow;
 use log::{warn, LevelFilter};
+use protocol::{
+    boss_bar::{BossBarColor, BossBarDivision},
+    types::GameMode,
+};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use toml::{value::Table, Value};
+
+use crate::favicon;
+
+/// Environment variables starting with this override config values, like `LIMBO_SERVER_PORT`
+/// for `port` in the `[server]` section.
+const ENV_PREFIX: &str = "LIMBO_";
 
 #[derive(Debug, Error)]
 pub enum ConfigError {
@@ -13,17 +23,82 @@ pub enum ConfigError {
     #[error("io error")]
     Io(#[from] std::io::Error),
 
-    #[error("deserialization error")]
+    #[error("failed to parse config")]
     DeserializationError(#[from] toml::de::Error),
 
     #[error("serialization error")]
     SerializationError(#[from] toml::ser::Error),
+
+    #[error("serialization error")]
+    JsonSerializationError(#[from] serde_json::Error),
+
+    #[error("invalid value for environment variable {name}")]
+    Override {
+        name: String,
+        #[source]
+        source: toml::de::Error,
+    },
+
+    #[error("invalid value for {key}: {reason}")]
+    Invalid {
+        key: &'static str,
+        reason: &'static str,
+    },
 }
 
 #[derive(Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
     pub server: Server,
     pub info: Info,
+
+    #[serde(default)]
+    pub world: World,
+
+    #[serde(default)]
+    pub boundaries: Boundaries,
+
+    #[serde(default)]
+    pub player: Player,
+
+    #[serde(default)]
+    pub transfer: Transfer,
+
+    #[serde(default)]
+    pub queue: Queue,
+
+    #[serde(default)]
+    pub idle: Idle,
+
+    #[serde(default)]
+    pub welcome: Welcome,
+
+    #[serde(default)]
+    pub tab_list: TabList,
+
+    #[serde(default)]
+    pub sidebar: Sidebar,
+
+    #[serde(default)]
+    pub chat: Chat,
+
+    #[serde(default)]
+    pub commands: Commands,
+
+    #[serde(default)]
+    pub rcon: Rcon,
+
+    #[serde(default)]
+    pub query: Query,
+
+    #[serde(default)]
+    pub metrics: Metrics,
+
+    #[serde(default)]
+    pub api: Api,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vhost: Vec<VirtualHost>,
 }
 
 impl Config {
@@ -33,13 +108,67 @@ impl Config {
         file.write_all(out.as_bytes())?;
         Ok(())
     }
+
+    pub fn vhost(&self, index: Option<usize>) -> Option<&VirtualHost> {
+        index.and_then(|index| self.vhost.get(index))
+    }
+
+    /// Checks for values that parse fine, but don't make sense.
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let checks = [
+            (
+                self.transfer.release_rate > 0.0,
+                "transfer.release_rate",
+                "must be positive",
+            ),
+            (
+                self.queue.release_rate > 0.0,
+                "queue.release_rate",
+                "must be positive",
+            ),
+            (
+                self.tab_list.refresh_interval > 0,
+                "tab_list.refresh_interval",
+                "must be positive",
+            ),
+            (
+                self.sidebar.refresh_interval > 0,
+                "sidebar.refresh_interval",
+                "must be positive",
+            ),
+            (
+                (0.0..=1.0).contains(&self.welcome.boss_bar_progress),
+                "welcome.boss_bar_progress",
+                "must be between 0 and 1",
+            ),
+            (
+                !self.idle.enabled || self.idle.warning < self.idle.timeout,
+                "idle.warning",
+                "must be shorter than idle.timeout",
+            ),
+            (
+                self.chat.cooldown >= 0.0,
+                "chat.cooldown",
+                "must not be negative",
+            ),
+        ];
+
+        match checks.into_iter().find(|(valid, _, _)| !valid) {
+            Some((_, key, reason)) => Err(ConfigError::Invalid { key, reason }),
+            None => Ok(()),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Server {
     pub host: String,
     pub port: u16,
     pub log_level: LevelFilter,
+
+    #[serde(default)]
+    pub reload_on_change: bool,
 }
 
 impl Default for Server {
@@ -48,11 +177,13 @@ impl Default for Server {
             host: "0.0.0.0".to_string(),
             port: 25565,
             log_level: LevelFilter::Info,
+            reload_on_change: false,
         }
     }
 }
 
-#[derive(Serialize, Deserialize)]
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct Info {
     pub hidden: bool,
     pub max_players: isize,
@@ -61,38 +192,89 @@ pub struct Info {
     pub name: String,
     pub icon: String,
 
+    /// MOTDs to rotate through, instead of `motd`.
+    #[serde(default)]
+    pub motds: Vec<String>,
+    /// Icons to rotate through, instead of `icon`. They're rotated separately from the MOTDs,
+    /// so any MOTD can be shown with any icon.
+    #[serde(default)]
+    pub icons: Vec<String>,
+    /// `sequential` steps through the MOTDs and icons in order, while `random` picks each of
+    /// them at random.
+    #[serde(default)]
+    pub rotation: Rotation,
+
+    /// Shows the names of online players when hovering over the player count.
+    #[serde(default = "default_player_sample")]
+    pub player_sample: bool,
+    /// Lines shown when hovering over the player count, instead of player names.
+    #[serde(default)]
+    pub hover: Vec<String>,
+
+    /// Replaces the version in the server list. Clients show it in red if the protocol doesn't
+    /// match their own, which is useful for something like "Maintenance".
+    pub version_name: Option<String>,
+    pub version_protocol: Option<usize>,
+
     #[serde(skip_serializing, skip_deserializing)]
-    icon_cache: Option<Vec<u8>>,
+    icon_cache: Option<Vec<Vec<u8>>>,
 }
 
 impl Info {
-    pub fn icon(&mut self) -> Option<&[u8]> {
-        if let Some(ref icon) = self.icon_cache {
-            return if icon.is_empty() { None } else { Some(icon) };
+    pub fn motds(&self) -> Vec<&str> {
+        if self.motds.is_empty() {
+            vec![&self.motd]
+        } else {
+            self.motds.iter().map(String::as_str).collect()
         }
+    }
 
-        let path = Path::new(&self.icon);
+    /// Loads the icons, skipping the ones that can't be read.
+    pub fn icons(&mut self) -> &[Vec<u8>] {
+        if self.icon_cache.is_none() {
+            let paths = if self.icons.is_empty() {
+                std::slice::from_ref(&self.icon)
+            } else {
+                &self.icons[..]
+            };
 
-        if !path.exists() {
-            warn!("server favicon file does not exist");
-            self.icon_cache = Some(Vec::new());
-            return None;
+            self.icon_cache = Some(
+                paths
+                    .iter()
+                    .filter_map(|path| favicon::load(Path::new(path)))
+                    .collect(),
+            );
         }
 
-        match std::fs::read(path) {
-            Ok(bytes) => {
-                self.icon_cache = Some(bytes);
-                self.icon_cache.as_ref().map(|v| &v[..])
-            }
-            Err(err) => {
-                warn!("failed to read server favicon file: {:#}", anyhow!(err));
-                self.icon_cache = Some(Vec::new());
-                None
-            }
+        self.icon_cache.as_deref().unwrap_or_default()
+    }
+}
+
+impl Info {
+    /// The info shown to players connecting through a virtual host.
+    pub fn for_vhost(&self, vhost: &VirtualHost) -> Info {
+        let mut info = self.clone();
+        if let Some(ref motd) = vhost.motd {
+            info.motd = motd.clone();
+            info.motds.clear();
+        }
+        if let Some(ref icon) = vhost.icon {
+            info.icon = icon.clone();
+            info.icons.clear();
+            info.icon_cache = None;
         }
+        if let Some(hidden) = vhost.hidden {
+            info.hidden = hidden;
+        }
+
+        info
     }
 }
 
+fn default_player_sample() -> bool {
+    true
+}
+
 impl Default for Info {
     fn default() -> Info {
         Info {
@@ -102,11 +284,593 @@ impl Default for Info {
             motd: "A Limbo Server".to_string(),
             name: "Limbo".to_string(),
             icon: "icon.png".to_string(),
+            motds: Vec::new(),
+            icons: Vec::new(),
+            rotation: Rotation::default(),
+            player_sample: true,
+            hover: Vec::new(),
+            version_name: None,
+            version_protocol: None,
             icon_cache: None,
         }
     }
 }
 
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct World {
+    pub schematic: Option<String>,
+    pub anvil: Option<String>,
+    pub origin: [i32; 3],
+    pub min_chunk: [i32; 2],
+    pub max_chunk: [i32; 2],
+    /// The `blocks.json` report of the vanilla 1.18.1 server. It's needed to load a schematic or
+    /// an Anvil world, and the server refuses to start without it if one is set.
+    pub block_report: String,
+    pub locked_time: Option<i64>,
+    pub weather: Weather,
+}
+
+impl Default for World {
+    fn default() -> World {
+        World {
+            schematic: None,
+            anvil: None,
+            origin: [0, 64, 0],
+            min_chunk: [-4, -4],
+            max_chunk: [3, 3],
+            block_report: "blocks.json".to_string(),
+            locked_time: None,
+            weather: Weather::Clear,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Boundaries {
+    pub min_y: Option<f64>,
+    pub radius: Option<f64>,
+    pub world_border: bool,
+}
+
+impl Default for Boundaries {
+    fn default() -> Boundaries {
+        Boundaries {
+            min_y: Some(0.0),
+            radius: None,
+            world_border: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Player {
+    pub game_mode: GameMode,
+    pub invulnerable: bool,
+    pub allow_flying: bool,
+    pub flying: bool,
+    pub flying_speed: f32,
+    pub walking_speed: f32,
+    pub frozen: bool,
+}
+
+impl Default for Player {
+    fn default() -> Player {
+        Player {
+            game_mode: GameMode::Survival,
+            invulnerable: false,
+            allow_flying: false,
+            flying: false,
+            flying_speed: 0.05,
+            walking_speed: 0.1,
+            frozen: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Transfer {
+    pub enabled: bool,
+    pub server: String,
+    pub host: String,
+    pub port: u16,
+    pub check_interval: u64,
+    pub release_rate: f64,
+    pub retry_interval: u64,
+    pub max_retries: u32,
+    pub waiting_message: Option<String>,
+    pub waiting_title: Option<String>,
+    pub waiting_subtitle: Option<String>,
+}
+
+impl Default for Transfer {
+    fn default() -> Transfer {
+        Transfer {
+            enabled: false,
+            server: "lobby".to_string(),
+            host: "127.0.0.1".to_string(),
+            port: 25566,
+            check_interval: 5,
+            release_rate: 2.0,
+            retry_interval: 10,
+            max_retries: 3,
+            waiting_message: Some(
+                "The server is unavailable right now, you'll be sent back once it's up again."
+                    .to_string(),
+            ),
+            waiting_title: None,
+            waiting_subtitle: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Queue {
+    pub enabled: bool,
+    pub server: String,
+    pub release_rate: f64,
+    pub grace_period: u64,
+    pub retry_interval: u64,
+    pub action_bar: Option<String>,
+    pub boss_bar: Option<String>,
+    pub boss_bar_color: BossBarColor,
+    pub tiers: Vec<QueueTier>,
+}
+
+impl Default for Queue {
+    fn default() -> Queue {
+        Queue {
+            enabled: false,
+            server: "main".to_string(),
+            release_rate: 1.0,
+            grace_period: 60,
+            retry_interval: 10,
+            action_bar: Some("Position in queue: {position}/{size}".to_string()),
+            boss_bar: Some("You are #{position} in the queue".to_string()),
+            boss_bar_color: BossBarColor::Green,
+            tiers: Vec::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct QueueTier {
+    pub name: String,
+    pub priority: u32,
+    pub players: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Idle {
+    pub enabled: bool,
+    pub timeout: u64,
+    pub warning: u64,
+    pub warning_message: Option<String>,
+    pub warning_title: Option<String>,
+    pub warning_subtitle: Option<String>,
+    pub kick_message: String,
+    pub transfer_server: Option<String>,
+    pub bypass: Vec<String>,
+}
+
+impl Default for Idle {
+    fn default() -> Idle {
+        Idle {
+            enabled: false,
+            timeout: 600,
+            warning: 30,
+            warning_message: Some(
+                "You will be kicked for being idle in {seconds} seconds.".to_string(),
+            ),
+            warning_title: None,
+            warning_subtitle: None,
+            kick_message: "You have been idle for too long.".to_string(),
+            transfer_server: None,
+            bypass: Vec::new(),
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Welcome {
+    pub messages: Vec<String>,
+    pub title: Option<String>,
+    pub subtitle: Option<String>,
+    pub fade_in: i32,
+    pub stay: i32,
+    pub fade_out: i32,
+    pub action_bar: Option<String>,
+    pub boss_bar: Option<String>,
+    pub boss_bar_color: BossBarColor,
+    pub boss_bar_division: BossBarDivision,
+    pub boss_bar_progress: f32,
+}
+
+impl Default for Welcome {
+    fn default() -> Welcome {
+        Welcome {
+            messages: Vec::new(),
+            title: None,
+            subtitle: None,
+            fade_in: 10,
+            stay: 70,
+            fade_out: 20,
+            action_bar: None,
+            boss_bar: None,
+            boss_bar_color: BossBarColor::Purple,
+            boss_bar_division: BossBarDivision::Progress,
+            boss_bar_progress: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TabList {
+    pub header: Option<String>,
+    pub footer: Option<String>,
+    pub refresh_interval: u64,
+    pub show_players: bool,
+}
+
+impl Default for TabList {
+    fn default() -> TabList {
+        TabList {
+            header: None,
+            footer: None,
+            refresh_interval: 20,
+            show_players: false,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Sidebar {
+    pub title: Option<String>,
+    pub lines: Vec<String>,
+    pub refresh_interval: u64,
+}
+
+impl Default for Sidebar {
+    fn default() -> Sidebar {
+        Sidebar {
+            title: None,
+            lines: Vec::new(),
+            refresh_interval: 20,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Chat {
+    pub mode: ChatMode,
+    pub format: String,
+    pub disabled_message: Option<String>,
+    pub cooldown: f64,
+    pub cooldown_message: Option<String>,
+    pub max_length: usize,
+    pub muted: Vec<String>,
+    pub muted_message: Option<String>,
+    pub log: bool,
+}
+
+impl Default for Chat {
+    fn default() -> Chat {
+        Chat {
+            mode: ChatMode::Disabled,
+            format: "<{player}> {message}".to_string(),
+            disabled_message: Some("Chat is disabled on this server.".to_string()),
+            cooldown: 1.0,
+            cooldown_message: Some("Please wait before sending another message.".to_string()),
+            max_length: 256,
+            muted: Vec::new(),
+            muted_message: Some("You are muted.".to_string()),
+            log: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChatMode {
+    Disabled,
+    Local,
+    Global,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Commands {
+    pub hub_server: Option<String>,
+    pub hub_aliases: Vec<String>,
+    pub servers: Vec<String>,
+    pub unknown_message: String,
+}
+
+impl Default for Commands {
+    fn default() -> Commands {
+        Commands {
+            hub_server: Some("lobby".to_string()),
+            hub_aliases: vec!["hub".to_string(), "lobby".to_string()],
+            servers: Vec::new(),
+            unknown_message: "Unknown command. Type /help for a list of commands.".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rcon {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub password: String,
+}
+
+impl Default for Rcon {
+    fn default() -> Rcon {
+        Rcon {
+            enabled: false,
+            host: "0.0.0.0".to_string(),
+            port: 25575,
+            password: String::new(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Query {
+    pub enabled: bool,
+    pub port: u16,
+}
+
+impl Default for Query {
+    fn default() -> Query {
+        Query {
+            enabled: false,
+            port: 25565,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Metrics {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+}
+
+impl Default for Metrics {
+    fn default() -> Metrics {
+        Metrics {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 9225,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Api {
+    pub enabled: bool,
+    pub host: String,
+    pub port: u16,
+    pub token: String,
+}
+
+impl Default for Api {
+    fn default() -> Api {
+        Api {
+            enabled: false,
+            host: "127.0.0.1".to_string(),
+            port: 8025,
+            token: String::new(),
+        }
+    }
+}
+
+/// Settings for players connecting with a specific hostname, which replace the ones in the rest
+/// of the config.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VirtualHost {
+    /// The hostnames this applies to, where `*` matches anything, like `*.example.com`.
+    pub hosts: Vec<String>,
+    pub motd: Option<String>,
+    pub icon: Option<String>,
+    pub hidden: Option<bool>,
+    pub spawn: Option<[i32; 3]>,
+    pub spawn_angle: Option<f32>,
+    /// Replaces the whole `[welcome]` section.
+    pub welcome: Option<Welcome>,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rotation {
+    #[default]
+    Sequential,
+    Random,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weather {
+    Clear,
+    Rain,
+    Thunder,
+}
+
+/// Reads the config file, applies overrides from the environment and the command line, and
+/// validates the result. Also returns the environment variables that were ignored, since they
+/// don't match any setting.
+pub fn load(path: &Path, port: Option<u16>) -> Result<(Config, Vec<String>), ConfigError> {
+    finish(read(path)?, port)
+}
+
+/// Like [`load`], but starting from the default config instead of a file.
+pub fn load_default(port: Option<u16>) -> Result<(Config, Vec<String>), ConfigError> {
+    finish(Config::default(), port)
+}
+
+/// Warns about the environment variables [`load`] ignored. This is left to the caller, since the
+/// config is loaded before logging is set up.
+pub fn warn_ignored(ignored: &[String]) {
+    for name in ignored {
+        warn!(
+            "ignoring environment variable {}, it doesn't match any setting",
+            name
+        );
+    }
+}
+
+fn finish(config: Config, port: Option<u16>) -> Result<(Config, Vec<String>), ConfigError> {
+    let (mut config, ignored) = apply_env(config)?;
+    if let Some(port) = port {
+        config.server.port = port;
+    }
+
+    config.validate()?;
+    Ok((config, ignored))
+}
+
+fn apply_env(mut config: Config) -> Result<(Config, Vec<String>), ConfigError> {
+    let mut vars = std::env::vars()
+        .filter(|(name, _)| name.starts_with(ENV_PREFIX))
+        .collect::<Vec<_>>();
+    if vars.is_empty() {
+        return Ok((config, Vec::new()));
+    }
+
+    vars.sort();
+
+    // Optional settings that aren't set are left out of TOML, but kept as nulls in JSON.
+    let mut known = HashSet::new();
+    settings(&serde_json::to_value(&config)?, "", &mut known);
+
+    let mut ignored = Vec::new();
+    let mut table = match Value::try_from(&config)? {
+        Value::Table(table) => table,
+        _ => Table::new(),
+    };
+    for (name, raw) in vars {
+        let path = locate(&table, &name[ENV_PREFIX.len()..].to_lowercase());
+
+        // Strings are taken as is, so a password like `1234` doesn't turn into a number. Other
+        // values are parsed, but can still be a string if the setting is optional and not set.
+        let mut values = Vec::new();
+        if !matches!(get(&table, &path), Some(Value::String(_))) {
+            values.extend(
+                toml::from_str::<Table>(&format!("value = {}", raw))
+                    .ok()
+                    .and_then(|mut parsed| parsed.remove("value")),
+            );
+        }
+        values.push(Value::String(raw));
+
+        // Deserializing after every variable lets us point out which one is wrong.
+        let mut error = None;
+        for value in values {
+            let mut updated = table.clone();
+            insert(&mut updated, &path, value);
+
+            match Value::Table(updated.clone()).try_into() {
+                Ok(updated_config) => {
+                    config = updated_config;
+                    table = updated;
+                    error = None;
+                    break;
+                }
+                Err(err) => {
+                    error.get_or_insert(err);
+                }
+            }
+        }
+
+        match error {
+            None => {}
+            Some(_) if !known.contains(&path.join(".")) => ignored.push(name),
+            Some(source) => return Err(ConfigError::Override { name, source }),
+        }
+    }
+
+    Ok((config, ignored))
+}
+
+/// Collects the keys of every setting, like `server.log_level`.
+fn settings(value: &serde_json::Value, prefix: &str, keys: &mut HashSet<String>) {
+    if let serde_json::Value::Object(object) = value {
+        for (key, value) in object {
+            let key = format!("{}{}", prefix, key);
+            settings(value, &format!("{}.", key), keys);
+            keys.insert(key);
+        }
+    }
+}
+
+/// Finds the setting an environment variable is for, where `key` is something like
+/// `server_log_level`. Sections and keys both use underscores, so the longest matching section
+/// is picked.
+fn locate(table: &Table, key: &str) -> Vec<String> {
+    let section = table
+        .iter()
+        .filter_map(|(name, value)| match value {
+            Value::Table(inner)
+                if key
+                    .strip_prefix(name.as_str())
+                    .is_some_and(|rest| rest.starts_with('_')) =>
+            {
+                Some((name, inner))
+            }
+            _ => None,
+        })
+        .max_by_key(|(name, _)| name.len());
+
+    match section {
+        Some((name, inner)) => std::iter::once(name.clone())
+            .chain(locate(inner, &key[name.len() + 1..]))
+            .collect(),
+        None => vec![key.to_string()],
+    }
+}
+
+fn get<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+    match path {
+        [key] => table.get(key),
+        [section, rest @ ..] => match table.get(section) {
+            Some(Value::Table(inner)) => get(inner, rest),
+            _ => None,
+        },
+        [] => None,
+    }
+}
+
+fn insert(table: &mut Table, path: &[String], value: Value) {
+    match path {
+        [key] => {
+            table.insert(key.clone(), value);
+        }
+        [section, rest @ ..] => {
+            if let Some(Value::Table(inner)) = table.get_mut(section) {
+                insert(inner, rest, value);
+            }
+        }
+        [] => {}
+    }
+}
+
 pub fn read(path: &Path) -> Result<Config, ConfigError> {
     if !path.exists() {
         return Err(ConfigError::NotFound);
@@ -18,11 +18,21 @@ use tokio::{
     net::TcpStream,
 };
 
+use crate::metrics::{self, Direction};
+
+/// The first byte of a server list ping from clients older than 1.7.
+const LEGACY_PING: u8 = 0xfe;
+
+const LEGACY_KICK: u8 = 0xff;
+
 #[derive(Debug, Error)]
 pub enum ReceiveError {
     #[error("connection closed")]
     ConnectionClosed,
 
+    #[error("legacy server list ping")]
+    LegacyPing { host: Option<String> },
+
     #[error(
         "invalid reported decompressed packet length (reported: {reported}, actual: {actual})"
     )]
@@ -62,13 +72,15 @@ pub struct Connection {
     compression_buf: Vec<u8>,
     staging_buf: Vec<u8>,
     buffer: BytesMut,
-    pub state: State,
+    state: State,
     pub compression_threshold: Option<usize>,
     cipher: Option<(Cfb8<Aes128>, Cfb8<Aes128>)>,
 }
 
 impl Connection {
     pub fn new(stream: TcpStream) -> Connection {
+        metrics::connection_opened(State::Handshake);
+
         Connection {
             stream: BufWriter::new(stream),
             packet_buf: Vec::new(),
@@ -81,6 +93,15 @@ impl Connection {
         }
     }
 
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn set_state(&mut self, state: State) {
+        metrics::state_changed(self.state, state);
+        self.state = state;
+    }
+
     pub fn update_encryption(&mut self, shared_secret: &[u8]) -> Result<(), InvalidLength> {
         self.cipher = Some((
             Cfb8::new_from_slices(shared_secret, shared_secret)?,
@@ -113,6 +134,26 @@ impl Connection {
     }
 
     pub fn parse_packet(&mut self) -> Result<Option<ClientPacket>, ReceiveError> {
+        // A handshake with a length of 254 also starts with `0xfe 0x01`, but handshakes are
+        // hardly ever that long. Vanilla servers make the same tradeoff.
+        if matches!(self.state, State::Handshake)
+            && self.buffer.first() == Some(&LEGACY_PING)
+            && matches!(self.buffer.get(1), None | Some(0x01))
+        {
+            // 1.6 clients follow up with a plugin message, which has to be read in full before
+            // answering, or the connection is reset when it's closed.
+            if self.buffer.get(2) == Some(&0xfa)
+                && legacy_ping_length(&self.buffer).is_none_or(|len| self.buffer.len() < len)
+            {
+                return Ok(None);
+            }
+
+            metrics::packet(Direction::Inbound, "LegacyPing", self.buffer.len());
+            let host = legacy_ping_host(&self.buffer);
+            self.buffer.clear();
+            return Err(ReceiveError::LegacyPing { host });
+        }
+
         let (offset, length) = {
             let mut buf = Cursor::new(&self.buffer[..]);
             if let Ok(length) = VarInt::read_from(&mut buf) {
@@ -157,6 +198,11 @@ impl Connection {
 
         self.buffer.advance(length);
 
+        let name = packet
+            .as_ref()
+            .map_or("Unrecognized", |packet| packet.name());
+        metrics::packet(Direction::Inbound, name, offset + length);
+
         // We defer the propagation of the error to correctly ignore unrecognized packets.
         let packet = packet?;
         trace!("received packet: {:?}", packet);
@@ -186,6 +232,7 @@ impl Connection {
 
         self.stream.write_all(&self.staging_buf).await?;
         self.stream.flush().await?;
+        metrics::packet(Direction::Outbound, packet.name(), self.staging_buf.len());
         self.staging_buf.clear();
 
         trace!("sent packet: {:?}", packet);
@@ -194,6 +241,61 @@ impl Connection {
 
         Ok(())
     }
+
+    /// Answers a legacy server list ping with a kick packet, which is how those clients get the
+    /// server's info.
+    pub async fn write_legacy_kick(&mut self, reason: &str) -> Result<(), SendError> {
+        let reason = reason.encode_utf16().collect::<Vec<_>>();
+
+        let mut packet = Vec::with_capacity(3 + reason.len() * 2);
+        packet.push(LEGACY_KICK);
+        packet.extend_from_slice(&(reason.len() as u16).to_be_bytes());
+        for unit in reason {
+            packet.extend_from_slice(&unit.to_be_bytes());
+        }
+
+        self.stream.write_all(&packet).await?;
+        self.stream.flush().await?;
+        metrics::packet(Direction::Outbound, "LegacyKick", packet.len());
+
+        Ok(())
+    }
+}
+
+impl Drop for Connection {
+    fn drop(&mut self) {
+        metrics::connection_closed(self.state);
+    }
+}
+
+fn u16_at(data: &[u8], offset: usize) -> Option<usize> {
+    data.get(offset..offset + 2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]) as usize)
+}
+
+/// The length of a 1.6 ping along with its plugin message on `MC|PingHost`, once enough of it
+/// has arrived to tell.
+fn legacy_ping_length(data: &[u8]) -> Option<usize> {
+    let channel = u16_at(data, 3)? * 2;
+    Some(5 + channel + 2 + u16_at(data, 5 + channel)?)
+}
+
+/// Reads the hostname 1.6 clients send along with their ping.
+fn legacy_ping_host(data: &[u8]) -> Option<String> {
+    if data.get(2) != Some(&0xfa) {
+        return None;
+    }
+
+    // Skip the channel name, the length of the message and the protocol version.
+    let offset = 5 + u16_at(data, 3)? * 2 + 2 + 1;
+    let length = u16_at(data, offset)?;
+    let host = data
+        .get(offset + 2..offset + 2 + length * 2)?
+        .chunks_exact(2)
+        .map(|bytes| u16::from_be_bytes([bytes[0], bytes[1]]))
+        .collect::<Vec<_>>();
+
+    String::from_utf16(&host).ok()
 }
 
 // TODO: Ideally, these should be struct methods, but the borrow checker doesn't like that.
@@ -211,6 +313,7 @@ fn stage_compressed_packet_into(
         .write_all(packet_buf)
         .map_err(SendError::Compression)?;
     encoder.finish().map_err(SendError::Compression)?;
+    metrics::compressed(length, compression_buf.len());
 
     stage_packet_into(staging_buf, compression_buf)?;
     compression_buf.clear();
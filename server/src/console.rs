@@ -0,0 +1,86 @@
+use std::{sync::mpsc as std_mpsc, thread};
+
+use anyhow::anyhow;
+use rustyline::{error::ReadlineError, Editor, ExternalPrinter};
+use tokio::sync::mpsc;
+
+const PROMPT: &str = "> ";
+
+/// A line typed into the console, along with a way to answer it.
+pub struct ConsoleCommand {
+    pub line: String,
+    pub response: std_mpsc::Sender<String>,
+}
+
+/// Starts reading commands from stdin on a separate thread. Returns where log lines should be
+/// sent, so that they're printed above the prompt instead of through it, or nothing if stdin
+/// can't be used as a console.
+pub fn start(commands: mpsc::Sender<ConsoleCommand>) -> Option<std_mpsc::Sender<String>> {
+    let (ready_send, ready) = std_mpsc::channel();
+
+    thread::spawn(move || {
+        let mut editor = match Editor::<()>::new() {
+            Ok(editor) => editor,
+            Err(err) => {
+                eprintln!("failed to start console: {:#}", anyhow!(err));
+                let _ = ready_send.send(None);
+                return;
+            }
+        };
+
+        let mut printer = match editor.create_external_printer() {
+            Ok(printer) => printer,
+            Err(err) => {
+                eprintln!("failed to start console: {:#}", anyhow!(err));
+                let _ = ready_send.send(None);
+                return;
+            }
+        };
+
+        let (logs, log_queue) = std_mpsc::channel::<String>();
+        let _ = ready_send.send(Some(logs));
+
+        thread::spawn(move || {
+            for line in log_queue {
+                let _ = printer.print(line);
+            }
+        });
+
+        loop {
+            let line = match editor.readline(PROMPT) {
+                Ok(line) => line,
+                // Ctrl+C in the console should still stop the server.
+                Err(ReadlineError::Interrupted) => "stop".to_string(),
+                Err(ReadlineError::Eof) => break,
+                Err(err) => {
+                    eprintln!("failed to read console input: {:#}", anyhow!(err));
+                    break;
+                }
+            };
+
+            let line = line.trim().to_string();
+            if line.is_empty() {
+                continue;
+            }
+
+            editor.add_history_entry(&line);
+
+            let (response_send, response) = std_mpsc::channel();
+            let command = ConsoleCommand {
+                line,
+                response: response_send,
+            };
+            if commands.blocking_send(command).is_err() {
+                break;
+            }
+
+            if let Ok(response) = response.recv() {
+                if !response.is_empty() {
+                    println!("{}", response);
+                }
+            }
+        }
+    });
+
+    ready.recv().ok().flatten()
+}
@@ -0,0 +1,84 @@
+use std::{io::Cursor, path::Path};
+
+use anyhow::anyhow;
+use image::{imageops::FilterType, ImageFormat};
+use log::warn;
+
+/// The size of server icons, anything else isn't shown by the client.
+const SIZE: u32 = 64;
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// Loads a server icon, converting it to a 64x64 PNG if it isn't one already.
+pub fn load(path: &Path) -> Option<Vec<u8>> {
+    if !path.exists() {
+        warn!("server favicon file does not exist ({})", path.display());
+        return None;
+    }
+
+    let bytes = match std::fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            warn!(
+                "failed to read server favicon file ({}): {:#}",
+                path.display(),
+                anyhow!(err)
+            );
+            return None;
+        }
+    };
+
+    if png_size(&bytes) == Some((SIZE, SIZE)) {
+        return Some(bytes);
+    }
+
+    match convert(&bytes) {
+        Ok((format, (width, height), converted)) => {
+            warn!(
+                "server favicon ({}) is a {}x{} {} image, it was converted to a {}x{} PNG",
+                path.display(),
+                width,
+                height,
+                format.extensions_str()[0].to_uppercase(),
+                SIZE,
+                SIZE
+            );
+            Some(converted)
+        }
+        Err(err) => {
+            warn!(
+                "server favicon ({}) is not a {}x{} PNG and could not be converted: {:#}",
+                path.display(),
+                SIZE,
+                SIZE,
+                anyhow!(err)
+            );
+            None
+        }
+    }
+}
+
+/// Reads the dimensions from the header of a PNG, without decoding it.
+fn png_size(bytes: &[u8]) -> Option<(u32, u32)> {
+    // The IHDR chunk always comes first, right after the signature and the chunk length.
+    if bytes.len() < 24 || bytes[..8] != PNG_SIGNATURE || &bytes[12..16] != b"IHDR" {
+        return None;
+    }
+
+    let width = u32::from_be_bytes(bytes[16..20].try_into().ok()?);
+    let height = u32::from_be_bytes(bytes[20..24].try_into().ok()?);
+    Some((width, height))
+}
+
+fn convert(bytes: &[u8]) -> image::ImageResult<(ImageFormat, (u32, u32), Vec<u8>)> {
+    let format = image::guess_format(bytes)?;
+    let image = image::load_from_memory_with_format(bytes, format)?;
+    let size = (image.width(), image.height());
+
+    // Cropping to a square keeps icons that aren't square from getting stretched.
+    let resized = image.resize_to_fill(SIZE, SIZE, FilterType::Lanczos3);
+
+    let mut png = Vec::new();
+    resized.write_to(&mut Cursor::new(&mut png), ImageFormat::Png)?;
+    Ok((format, size, png))
+}
@@ -1,10 +1,17 @@
+use std::sync::mpsc::Sender;
+
 use fern::{
     colors::{Color, ColoredLevelConfig},
-    Dispatch,
+    Dispatch, Output,
 };
 use log::LevelFilter;
 
-pub fn init(level_filter: LevelFilter) -> Result<(), fern::InitError> {
+/// Sets up logging to stdout, or to the console if there is one, so log lines don't end up in
+/// the middle of the prompt.
+pub fn init(
+    level_filter: LevelFilter,
+    console: Option<Sender<String>>,
+) -> Result<(), fern::InitError> {
     let colors = ColoredLevelConfig::new()
         .trace(Color::Cyan)
         .debug(Color::Blue)
@@ -24,7 +31,10 @@ pub fn init(level_filter: LevelFilter) -> Result<(), fern::InitError> {
         .level(level_filter)
         .level_for("ureq", LevelFilter::Info)
         .level_for("rustls", LevelFilter::Info)
-        .chain(std::io::stdout())
+        .chain(match console {
+            Some(console) => Output::from(console),
+            None => Output::from(std::io::stdout()),
+        })
         .apply()?;
 
     Ok(())
@@ -1,55 +1,137 @@
-use std::{path::Path, sync::Arc};
+use std::{
+    io::IsTerminal,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
 
 use anyhow::anyhow;
+use clap::Parser;
 use client::Client;
 use connection::Connection;
 use log::{debug, error, info, warn, LevelFilter};
 use tokio::{
     net::TcpListener,
     select, signal,
-    sync::{broadcast, mpsc::channel, RwLock},
+    sync::{
+        broadcast,
+        mpsc::{self, channel},
+    },
 };
 
 use crate::{
+    bans::BanList,
     config::{Config, ConfigError},
     shutdown::Shutdown,
+    state::ServerState,
+    world::World,
 };
 
+mod admin;
+mod api;
+mod bans;
 mod client;
+mod commands;
 mod config;
 mod connection;
+mod console;
+mod favicon;
 mod logging;
+mod metrics;
 mod mojang;
+mod players;
+mod proxy;
+mod query;
+mod queue;
+mod rcon;
+mod reload;
 mod shutdown;
+mod sidebar;
+mod state;
+mod status;
+mod tab_list;
+mod tick;
+mod transfer;
+mod vhost;
+mod world;
+
+const BANS_PATH: &str = "bans.json";
+
+/// A simple, very minimal Minecraft server.
+#[derive(Parser)]
+#[clap(version)]
+struct Args {
+    /// The config file to use, which is created if it does not exist yet.
+    #[clap(long, value_parser, default_value = "limbo.toml")]
+    config: PathBuf,
 
-const CONFIG_PATH: &str = "limbo.toml";
+    /// The port to listen on, instead of the one in the config file.
+    #[clap(long, value_parser)]
+    port: Option<u16>,
+
+    /// Writes the default config file and exits.
+    #[clap(long, action)]
+    generate_config: bool,
+}
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let args = Args::parse();
+
+    if args.generate_config {
+        Config::default()
+            .write(&args.config)
+            .map_err(|err| anyhow!(err).context("failed to write config file"))?;
+        println!("wrote default config file ({})", args.config.display());
+        return Ok(());
+    }
+
     #[cfg(feature = "console")]
     console_subscriber::init();
 
-    let config = match config::read(Path::new(CONFIG_PATH)) {
-        Ok(config) => {
-            logging::init(config.server.log_level)?;
-            config
-        }
+    let (config, not_found) = match config::load(&args.config, args.port) {
+        Err(ConfigError::NotFound) => (config::load_default(args.port), true),
+        config => (config, false),
+    };
+
+    // This happens before the console is started, so an invalid config doesn't leave the
+    // terminal in raw mode.
+    let (config, ignored) = match config {
+        Ok(config) => config,
         Err(err) => {
-            logging::init(LevelFilter::Info)?;
+            logging::init(LevelFilter::Info, None)?;
+            error!(
+                "invalid config file ({}): {:#}",
+                args.config.display(),
+                anyhow!(err)
+            );
+            std::process::exit(1);
+        }
+    };
 
-            let not_found = matches!(err, ConfigError::NotFound);
-            warn!("failed to read config file: {:#}", anyhow!(err));
+    let (console_send, mut console_commands) = mpsc::channel(8);
+    let console = if std::io::stdin().is_terminal() {
+        console::start(console_send)
+    } else {
+        None
+    };
 
-            let config = Config::default();
-            if not_found {
-                if let Err(err) = config.write(Path::new(CONFIG_PATH)) {
-                    warn!("failed to create new config file: {:#}", anyhow!(err));
-                } else {
-                    info!("intialized default config file ({})", CONFIG_PATH);
-                }
-            }
+    logging::init(config.server.log_level, console)?;
+    config::warn_ignored(&ignored);
 
-            config
+    if not_found {
+        if let Err(err) = Config::default().write(&args.config) {
+            warn!("failed to create new config file: {:#}", anyhow!(err));
+        } else {
+            info!("intialized default config file ({})", args.config.display());
+        }
+    }
+
+    // Serving the void instead of the configured world would go unnoticed, so this is fatal.
+    let world = match World::load(&config.world) {
+        Ok(world) => world,
+        Err(err) => {
+            error!("failed to load world: {:#}", anyhow!(err));
+            std::process::exit(1);
         }
     };
 
@@ -60,11 +142,102 @@ async fn main() -> anyhow::Result<()> {
         config.server.host, config.server.port
     );
 
-    let config = Arc::new(RwLock::new(config));
+    let transfer = config.transfer.enabled;
+    let queue = config.queue.enabled;
+    let query = config.query.enabled;
+    let reload_on_change = config.server.reload_on_change;
+    let metrics = config.metrics.enabled;
+    let api = config.api.enabled && !config.api.token.is_empty();
+    if config.api.enabled && !api {
+        warn!("not starting the api, it needs a token to be set");
+    }
+    let rcon = config.rcon.enabled && !config.rcon.password.is_empty();
+    if config.rcon.enabled && !rcon {
+        warn!("not starting rcon, it needs a password to be set");
+    }
+    let bans = BanList::load(Path::new(BANS_PATH)).unwrap_or_else(|err| {
+        error!("failed to load ban list: {:#}", anyhow!(err));
+        BanList::empty(Path::new(BANS_PATH))
+    });
+
+    let state = Arc::new(ServerState::new(
+        args.config,
+        args.port,
+        config,
+        world,
+        bans,
+    ));
 
     let (shutdown, _) = broadcast::channel::<()>(1);
     let (done_send, mut done) = channel::<()>(1);
 
+    tokio::spawn(tick::run(
+        state.ticks.clone(),
+        Shutdown::new(shutdown.subscribe()),
+    ));
+
+    #[cfg(unix)]
+    tokio::spawn(reload::hangup(
+        state.clone(),
+        Shutdown::new(shutdown.subscribe()),
+    ));
+
+    if reload_on_change {
+        tokio::spawn(reload::watch(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    if transfer {
+        tokio::spawn(transfer::run(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    if queue {
+        tokio::spawn(queue::run(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    if query {
+        tokio::spawn(query::run(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    if metrics {
+        tokio::spawn(metrics::run(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    if api {
+        tokio::spawn(api::run(state.clone(), Shutdown::new(shutdown.subscribe())));
+    }
+
+    if rcon {
+        tokio::spawn(rcon::run(
+            state.clone(),
+            Shutdown::new(shutdown.subscribe()),
+        ));
+    }
+
+    tokio::spawn({
+        let state = state.clone();
+        async move {
+            while let Some(command) = console_commands.recv().await {
+                let response = admin::execute(&state, &command.line).await;
+                let _ = command.response.send(response);
+            }
+        }
+    });
+
     loop {
         select! {
             res = listener.accept() => {
@@ -72,19 +245,20 @@ async fn main() -> anyhow::Result<()> {
                     Ok((stream, address)) => {
                         debug!("new connection from {}", address);
 
-                        let config = config.clone();
+                        let state = state.clone();
                         let shutdown = Shutdown::new(shutdown.subscribe());
                         let done = done_send.clone();
 
                         tokio::spawn(async move {
-                            let mut client = Client::new(Connection::new(stream), config, shutdown, done);
+                            let mut client = Client::new(Connection::new(stream), address, state, shutdown, done);
                             client.run().await;
                         });
                     },
                     Err(err) => error!("failed to accept connection: {:#}", anyhow!(err)),
                 }
             }
-            _ = signal::ctrl_c() => break
+            _ = signal::ctrl_c() => break,
+            _ = state.stop.notified() => break
         }
     }
 
@@ -0,0 +1,228 @@
+use std::{convert::Infallible, sync::Arc};
+
+use anyhow::anyhow;
+use hyper::{
+    header::CONTENT_TYPE,
+    service::{make_service_fn, service_fn},
+    Body, Method, Request, Response, StatusCode,
+};
+use log::{debug, error, info};
+use once_cell::sync::Lazy;
+use prometheus::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge_vec,
+    Encoder, Histogram, IntCounter, IntCounterVec, IntGaugeVec, TextEncoder,
+};
+use protocol::packets::State;
+use tokio::net::TcpListener;
+
+use crate::{shutdown::Shutdown, state::ServerState};
+
+static CONNECTIONS: Lazy<IntGaugeVec> = Lazy::new(|| {
+    register_int_gauge_vec!(
+        "limbo_connections",
+        "Open connections by protocol state.",
+        &["state"]
+    )
+    .unwrap()
+});
+
+static LOGINS_SUCCEEDED: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!("limbo_logins_succeeded_total", "Players that logged in.").unwrap()
+});
+
+static LOGINS_FAILED: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "limbo_logins_failed_total",
+        "Login attempts that were refused, by reason.",
+        &["reason"]
+    )
+    .unwrap()
+});
+
+static AUTHENTICATION_DURATION: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "limbo_authentication_duration_seconds",
+        "Time spent authenticating players with the session server.",
+        vec![0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0]
+    )
+    .unwrap()
+});
+
+static PACKETS: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "limbo_packets_total",
+        "Packets received and sent, by packet type.",
+        &["direction", "packet"]
+    )
+    .unwrap()
+});
+
+static PACKET_BYTES: Lazy<IntCounterVec> = Lazy::new(|| {
+    register_int_counter_vec!(
+        "limbo_packet_bytes_total",
+        "Bytes received and sent on the wire, by packet type.",
+        &["direction", "packet"]
+    )
+    .unwrap()
+});
+
+static COMPRESSION_RATIO: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "limbo_compression_ratio",
+        "Compressed size of outgoing packets relative to their uncompressed size.",
+        vec![0.05, 0.1, 0.2, 0.3, 0.4, 0.5, 0.6, 0.7, 0.8, 0.9, 1.0]
+    )
+    .unwrap()
+});
+
+static KEEP_ALIVE_RTT: Lazy<Histogram> = Lazy::new(|| {
+    register_histogram!(
+        "limbo_keep_alive_rtt_seconds",
+        "Round trip time of keep-alives.",
+        vec![0.01, 0.025, 0.05, 0.1, 0.15, 0.25, 0.5, 1.0, 2.5, 5.0]
+    )
+    .unwrap()
+});
+
+static STATUS_REQUESTS: Lazy<IntCounter> = Lazy::new(|| {
+    register_int_counter!(
+        "limbo_status_requests_total",
+        "Status requests from the server list."
+    )
+    .unwrap()
+});
+
+/// The direction of a packet, from the point of view of the server.
+#[derive(Debug, Clone, Copy)]
+pub enum Direction {
+    Inbound,
+    Outbound,
+}
+
+impl Direction {
+    fn label(self) -> &'static str {
+        match self {
+            Direction::Inbound => "in",
+            Direction::Outbound => "out",
+        }
+    }
+}
+
+fn state_label(state: State) -> &'static str {
+    match state {
+        State::Handshake => "handshake",
+        State::Status => "status",
+        State::Login => "login",
+        State::Play => "play",
+    }
+}
+
+pub fn connection_opened(state: State) {
+    CONNECTIONS.with_label_values(&[state_label(state)]).inc();
+}
+
+pub fn connection_closed(state: State) {
+    CONNECTIONS.with_label_values(&[state_label(state)]).dec();
+}
+
+pub fn state_changed(from: State, to: State) {
+    connection_closed(from);
+    connection_opened(to);
+}
+
+pub fn login_succeeded() {
+    LOGINS_SUCCEEDED.inc();
+}
+
+pub fn login_failed(reason: &str) {
+    LOGINS_FAILED.with_label_values(&[reason]).inc();
+}
+
+pub fn authentication_timer() -> prometheus::HistogramTimer {
+    AUTHENTICATION_DURATION.start_timer()
+}
+
+pub fn packet(direction: Direction, packet: &str, bytes: usize) {
+    PACKETS
+        .with_label_values(&[direction.label(), packet])
+        .inc();
+    PACKET_BYTES
+        .with_label_values(&[direction.label(), packet])
+        .inc_by(bytes as u64);
+}
+
+pub fn compressed(uncompressed: usize, compressed: usize) {
+    COMPRESSION_RATIO.observe(compressed as f64 / uncompressed as f64);
+}
+
+pub fn keep_alive(rtt_millis: u32) {
+    KEEP_ALIVE_RTT.observe(rtt_millis as f64 / 1000.0);
+}
+
+pub fn status_request() {
+    STATUS_REQUESTS.inc();
+}
+
+/// Serves the metrics in the Prometheus text format on `/metrics`.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let (host, port) = {
+        let config = state.config.read().await;
+        (config.metrics.host.clone(), config.metrics.port)
+    };
+
+    let listener = match TcpListener::bind((host.as_str(), port))
+        .await
+        .and_then(|listener| listener.into_std())
+    {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("failed to start metrics listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    let server = match hyper::Server::from_tcp(listener) {
+        Ok(server) => server,
+        Err(err) => {
+            error!("failed to start metrics listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    info!("listening on {}:{} for metrics requests", host, port);
+
+    let service = make_service_fn(|_| async { Ok::<_, Infallible>(service_fn(serve)) });
+    let server = server
+        .serve(service)
+        .with_graceful_shutdown(async move { shutdown.recv().await });
+
+    if let Err(err) = server.await {
+        error!("metrics listener failed: {:#}", anyhow!(err));
+    }
+
+    debug!("halting metrics listener");
+}
+
+async fn serve(request: Request<Body>) -> Result<Response<Body>, Infallible> {
+    if request.method() != Method::GET || request.uri().path() != "/metrics" {
+        return Ok(Response::builder()
+            .status(StatusCode::NOT_FOUND)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    if let Err(err) = encoder.encode(&prometheus::gather(), &mut buffer) {
+        error!("failed to encode metrics: {:#}", anyhow!(err));
+        return Ok(Response::builder()
+            .status(StatusCode::INTERNAL_SERVER_ERROR)
+            .body(Body::empty())
+            .unwrap());
+    }
+
+    Ok(Response::builder()
+        .header(CONTENT_TYPE, encoder.format_type())
+        .body(Body::from(buffer))
+        .unwrap())
+}
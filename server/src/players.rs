@@ -0,0 +1,159 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::{
+        atomic::{AtomicU32, AtomicU64, Ordering},
+        Arc, RwLock,
+    },
+    time::SystemTime,
+};
+
+use log::debug;
+use protocol::{packets::server::ServerPacket, player_info::AddPlayerProperty};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use uuid::Uuid;
+
+use crate::client::ClientEvent;
+
+static NEXT_CONNECTION_ID: AtomicU64 = AtomicU64::new(0);
+
+/// A way to reach a logged in client from outside of its connection task.
+#[derive(Clone)]
+pub struct PlayerHandle {
+    connection_id: u64,
+    pub name: String,
+    pub uuid: Uuid,
+    pub address: SocketAddr,
+    pub joined_at: SystemTime,
+    pub properties: Vec<AddPlayerProperty>,
+    ping: Arc<AtomicU32>,
+    brand: Arc<RwLock<Option<String>>>,
+    events: mpsc::Sender<ClientEvent>,
+}
+
+impl PlayerHandle {
+    pub fn new(
+        name: String,
+        uuid: Uuid,
+        address: SocketAddr,
+        events: mpsc::Sender<ClientEvent>,
+    ) -> PlayerHandle {
+        PlayerHandle {
+            connection_id: NEXT_CONNECTION_ID.fetch_add(1, Ordering::Relaxed),
+            name,
+            uuid,
+            address,
+            joined_at: SystemTime::now(),
+            properties: Vec::new(),
+            ping: Arc::new(AtomicU32::new(0)),
+            brand: Arc::new(RwLock::new(None)),
+            events,
+        }
+    }
+
+    /// Queues an event for the client, returning whether it was accepted. Events are dropped if
+    /// the client is gone or too far behind, so that one slow client can't hold up the server.
+    pub fn send(&self, event: ClientEvent) -> bool {
+        match self.events.try_send(event) {
+            Ok(()) => true,
+            Err(TrySendError::Full(_)) => {
+                debug!("dropped event for {}, queue is full", self.name);
+                false
+            }
+            Err(TrySendError::Closed(_)) => false,
+        }
+    }
+
+    /// The round trip time of the last keep-alive, in milliseconds.
+    pub fn ping(&self) -> u32 {
+        self.ping.load(Ordering::Relaxed)
+    }
+
+    pub fn set_ping(&self, ping: u32) {
+        self.ping.store(ping, Ordering::Relaxed);
+    }
+
+    /// The client brand, like `vanilla` or `fabric`, once the client has sent it.
+    pub fn brand(&self) -> Option<String> {
+        self.brand.read().unwrap().clone()
+    }
+
+    pub fn set_brand(&self, brand: String) {
+        *self.brand.write().unwrap() = Some(brand);
+    }
+}
+
+#[derive(Default)]
+pub struct PlayerRegistry {
+    players: RwLock<HashMap<Uuid, PlayerHandle>>,
+}
+
+impl PlayerRegistry {
+    /// Adds a player, returning the handle of an earlier connection with the same UUID.
+    pub fn add(&self, handle: PlayerHandle) -> Option<PlayerHandle> {
+        self.players.write().unwrap().insert(handle.uuid, handle)
+    }
+
+    /// Removes a player, unless it has already been replaced by a newer connection.
+    pub fn remove(&self, handle: &PlayerHandle) {
+        let mut players = self.players.write().unwrap();
+        if matches!(players.get(&handle.uuid), Some(h) if h.connection_id == handle.connection_id) {
+            players.remove(&handle.uuid);
+        }
+    }
+
+    pub fn get(&self, uuid: &Uuid) -> Option<PlayerHandle> {
+        self.players.read().unwrap().get(uuid).cloned()
+    }
+
+    pub fn find(&self, name: &str) -> Option<PlayerHandle> {
+        self.players
+            .read()
+            .unwrap()
+            .values()
+            .find(|player| player.name.eq_ignore_ascii_case(name))
+            .cloned()
+    }
+
+    pub fn len(&self) -> usize {
+        self.players.read().unwrap().len()
+    }
+
+    /// Returns every online player, in the order they joined.
+    pub fn all(&self) -> Vec<PlayerHandle> {
+        let mut players = self
+            .players
+            .read()
+            .unwrap()
+            .values()
+            .cloned()
+            .collect::<Vec<_>>();
+        players.sort_by_key(|player| player.joined_at);
+        players
+    }
+
+    /// Sends a packet to every online player.
+    pub fn broadcast<F: Fn() -> ServerPacket>(&self, packet: F) {
+        for player in self.players.read().unwrap().values() {
+            player.send(ClientEvent::Packet(Box::new(packet())));
+        }
+    }
+
+    /// Sends a packet to every online player but one, usually the player the packet is about.
+    pub fn broadcast_except<F: Fn() -> ServerPacket>(&self, uuid: &Uuid, packet: F) {
+        for player in self.players.read().unwrap().values() {
+            if player.uuid != *uuid {
+                player.send(ClientEvent::Packet(Box::new(packet())));
+            }
+        }
+    }
+}
+
+/// Checks whether a player is in a configured list, which can contain either UUIDs or names.
+pub fn is_listed(list: &[String], uuid: &Uuid, name: &str) -> bool {
+    list.iter().any(|player| {
+        Uuid::parse_str(player)
+            .map(|id| id == *uuid)
+            .unwrap_or_else(|_| player.eq_ignore_ascii_case(name))
+    })
+}
@@ -0,0 +1,31 @@
+use std::io::{Read, Write};
+
+use protocol::{io::ShortPrefixedString, FieldReadError, FieldWriteError, PacketField};
+
+/// The plugin message channel understood by both BungeeCord and Velocity.
+pub const PROXY_CHANNEL: &str = "bungeecord:main";
+
+#[derive(Debug)]
+pub enum ProxyMessage {
+    Connect { server: String },
+}
+
+impl PacketField for ProxyMessage {
+    fn read_from(buffer: &mut dyn Read) -> Result<ProxyMessage, FieldReadError> {
+        match ShortPrefixedString::read_from(buffer)?.0.as_str() {
+            "Connect" => Ok(ProxyMessage::Connect {
+                server: ShortPrefixedString::read_from(buffer)?.0,
+            }),
+            subchannel => Err(FieldReadError::InvalidEnumId(subchannel.to_string())),
+        }
+    }
+
+    fn write_to(&self, buffer: &mut dyn Write) -> Result<(), FieldWriteError> {
+        match self {
+            ProxyMessage::Connect { server } => {
+                ShortPrefixedString("Connect".to_string()).write_to(buffer)?;
+                ShortPrefixedString(server.clone()).write_to(buffer)
+            }
+        }
+    }
+}
@@ -0,0 +1,167 @@
+use std::{
+    collections::HashMap,
+    net::SocketAddr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use log::{debug, error, info};
+use protocol::info::VERSION;
+use rand::{rngs::OsRng, Rng};
+use tokio::{net::UdpSocket, select, time};
+
+use crate::{shutdown::Shutdown, state::ServerState};
+
+const MAGIC: [u8; 2] = [0xfe, 0xfd];
+
+const TYPE_STAT: u8 = 0;
+const TYPE_HANDSHAKE: u8 = 9;
+
+/// How long a challenge token stays valid, the same as the vanilla server.
+const TOKEN_LIFETIME: Duration = Duration::from_secs(30);
+
+/// Answers GameSpy4 query requests, the UDP protocol behind `enable-query` on vanilla servers.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let (host, port) = {
+        let config = state.config.read().await;
+        (config.server.host.clone(), config.query.port)
+    };
+
+    let socket = match UdpSocket::bind((host.as_str(), port)).await {
+        Ok(socket) => socket,
+        Err(err) => {
+            error!("failed to start query listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    info!("listening on {}:{} for queries", host, port);
+
+    let mut tokens = HashMap::<SocketAddr, (i32, Instant)>::new();
+    let mut cleanup = time::interval(TOKEN_LIFETIME);
+    let mut buffer = [0u8; 1460];
+
+    loop {
+        select! {
+            res = socket.recv_from(&mut buffer) => {
+                let (length, address) = match res {
+                    Ok(res) => res,
+                    Err(err) => {
+                        debug!("failed to receive query: {:#}", anyhow!(err));
+                        continue;
+                    }
+                };
+
+                if let Some(response) = respond(&state, &buffer[..length], address, &mut tokens).await {
+                    if let Err(err) = socket.send_to(&response, address).await {
+                        debug!("failed to answer query from {}: {:#}", address, anyhow!(err));
+                    }
+                }
+            }
+            _ = cleanup.tick() => {
+                tokens.retain(|_, (_, created)| created.elapsed() < TOKEN_LIFETIME);
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting query listener");
+}
+
+async fn respond(
+    state: &ServerState,
+    request: &[u8],
+    address: SocketAddr,
+    tokens: &mut HashMap<SocketAddr, (i32, Instant)>,
+) -> Option<Vec<u8>> {
+    if request.len() < 7 || request[..2] != MAGIC {
+        return None;
+    }
+
+    let kind = request[2];
+    let session = &request[3..7];
+
+    let mut response = vec![kind];
+    response.extend_from_slice(session);
+
+    match kind {
+        TYPE_HANDSHAKE => {
+            let token = OsRng.gen_range(0..i32::MAX);
+            tokens.insert(address, (token, Instant::now()));
+
+            push_str(&mut response, &token.to_string());
+            Some(response)
+        }
+        TYPE_STAT if request.len() >= 11 => {
+            let token = i32::from_be_bytes([request[7], request[8], request[9], request[10]]);
+            match tokens.get(&address) {
+                Some(&(expected, created))
+                    if expected == token && created.elapsed() < TOKEN_LIFETIME => {}
+                _ => return None,
+            }
+
+            let config = state.config.read().await;
+            if config.info.hidden {
+                return None;
+            }
+
+            let players = if config.info.hide_player_count {
+                Vec::new()
+            } else {
+                state.players.all()
+            };
+            let online = players.len().to_string();
+            let max = if config.info.hide_player_count {
+                0
+            } else {
+                config.info.max_players.max(0)
+            }
+            .to_string();
+            let port = config.server.port;
+            let host = config.server.host.clone();
+
+            // A full stat request is padded with four extra bytes.
+            if request.len() >= 15 {
+                response.extend_from_slice(b"splitnum\0\x80\0");
+
+                for (key, value) in [
+                    ("hostname", config.info.motd.as_str()),
+                    ("gametype", "SMP"),
+                    ("game_id", "MINECRAFT"),
+                    ("version", &VERSION.name),
+                    ("plugins", ""),
+                    ("map", "world"),
+                    ("numplayers", &online),
+                    ("maxplayers", &max),
+                    ("hostport", &port.to_string()),
+                    ("hostip", &host),
+                ] {
+                    push_str(&mut response, key);
+                    push_str(&mut response, value);
+                }
+                response.push(0);
+
+                response.extend_from_slice(b"\x01player_\0\0");
+                for player in &players {
+                    push_str(&mut response, &player.name);
+                }
+                response.push(0);
+            } else {
+                for value in [config.info.motd.as_str(), "SMP", "world", &online, &max] {
+                    push_str(&mut response, value);
+                }
+                response.extend_from_slice(&port.to_le_bytes());
+                push_str(&mut response, &host);
+            }
+
+            Some(response)
+        }
+        _ => None,
+    }
+}
+
+fn push_str(buffer: &mut Vec<u8>, value: &str) {
+    buffer.extend_from_slice(value.as_bytes());
+    buffer.push(0);
+}
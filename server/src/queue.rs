@@ -0,0 +1,163 @@
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use log::{debug, info};
+use tokio::{select, time};
+use uuid::Uuid;
+
+use crate::{client::ClientEvent, config, players, shutdown::Shutdown, state::ServerState};
+
+struct Entry {
+    uuid: Uuid,
+    name: String,
+    priority: u32,
+    left_at: Option<Instant>,
+    released_at: Option<Instant>,
+}
+
+impl Entry {
+    fn is_waiting(&self) -> bool {
+        self.left_at.is_none() && self.released_at.is_none()
+    }
+}
+
+/// Players waiting to be sent to the target server, ordered by priority and then by the time
+/// they joined the queue.
+#[derive(Default)]
+pub struct Queue {
+    entries: Mutex<Vec<Entry>>,
+}
+
+impl Queue {
+    /// Adds a player to the back of their priority tier, returning whether they got their old
+    /// place back instead because they reconnected within the grace period.
+    pub fn join(&self, uuid: Uuid, name: String, priority: u32) -> bool {
+        let mut entries = self.entries.lock().unwrap();
+
+        if let Some(entry) = entries.iter_mut().find(|entry| entry.uuid == uuid) {
+            entry.name = name;
+            entry.left_at = None;
+            entry.released_at = None;
+            return true;
+        }
+
+        let index = entries
+            .iter()
+            .position(|entry| entry.priority < priority)
+            .unwrap_or(entries.len());
+        entries.insert(
+            index,
+            Entry {
+                uuid,
+                name,
+                priority,
+                left_at: None,
+                released_at: None,
+            },
+        );
+
+        false
+    }
+
+    /// Marks a player as gone. Their place is kept for the grace period, unless they were already
+    /// released, in which case they most likely made it to the target server.
+    pub fn leave(&self, uuid: &Uuid) {
+        let mut entries = self.entries.lock().unwrap();
+
+        if let Some(index) = entries.iter().position(|entry| entry.uuid == *uuid) {
+            if entries[index].released_at.is_some() {
+                entries.remove(index);
+            } else {
+                entries[index].left_at = Some(Instant::now());
+            }
+        }
+    }
+
+    /// Returns the position of a waiting player, starting at 1, and the number of waiting players.
+    pub fn position(&self, uuid: &Uuid) -> Option<(usize, usize)> {
+        let entries = self.entries.lock().unwrap();
+
+        let mut waiting = entries.iter().filter(|entry| entry.is_waiting());
+        let position = waiting.position(|entry| entry.uuid == *uuid)?;
+        Some((position + 1, position + 1 + waiting.count()))
+    }
+
+    /// Takes the first waiting player out of the queue, without forgetting about them yet.
+    fn release(&self) -> Option<(Uuid, String)> {
+        let mut entries = self.entries.lock().unwrap();
+
+        let entry = entries.iter_mut().find(|entry| entry.is_waiting())?;
+        entry.released_at = Some(Instant::now());
+        Some((entry.uuid, entry.name.clone()))
+    }
+
+    /// Forgets about players whose grace period is over, and puts players that were released but
+    /// are still here back in their old place.
+    fn expire(&self, grace_period: Duration, retry_interval: Duration) {
+        let mut entries = self.entries.lock().unwrap();
+
+        entries.retain(
+            |entry| !matches!(entry.left_at, Some(left_at) if left_at.elapsed() >= grace_period),
+        );
+
+        for entry in entries.iter_mut() {
+            if matches!(entry.released_at, Some(released_at) if released_at.elapsed() >= retry_interval)
+            {
+                debug!(
+                    "{} is still here after being released, requeueing",
+                    entry.name
+                );
+                entry.released_at = None;
+            }
+        }
+    }
+}
+
+/// Finds the priority of a player from the configured tiers, which can list either UUIDs or names.
+/// Players that aren't in any tier have a priority of 0.
+pub fn priority(config: &config::Queue, uuid: &Uuid, name: &str) -> u32 {
+    config
+        .tiers
+        .iter()
+        .filter(|tier| players::is_listed(&tier.players, uuid, name))
+        .map(|tier| tier.priority)
+        .max()
+        .unwrap_or(0)
+}
+
+/// Sends queued players to the target server through the proxy at the configured rate.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let release_interval = {
+        let config = state.config.read().await;
+        Duration::from_secs_f64(1.0 / config.queue.release_rate.max(0.01))
+    };
+
+    let mut release = time::interval(release_interval);
+
+    loop {
+        select! {
+            _ = release.tick() => {
+                let config = state.config.read().await;
+                state.queue.expire(
+                    Duration::from_secs(config.queue.grace_period),
+                    Duration::from_secs(config.queue.retry_interval),
+                );
+
+                if let Some((uuid, name)) = state.queue.release() {
+                    match state.players.get(&uuid) {
+                        Some(player) => {
+                            info!("releasing {} from the queue", name);
+                            player.send(ClientEvent::Transfer(config.queue.server.clone()));
+                        }
+                        None => state.queue.leave(&uuid),
+                    }
+                }
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting queue");
+}
@@ -0,0 +1,201 @@
+use std::{net::SocketAddr, sync::Arc};
+
+use anyhow::anyhow;
+use log::{debug, error, info, warn};
+use subtle::ConstantTimeEq;
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+    select,
+};
+
+use crate::{admin, shutdown::Shutdown, state::ServerState};
+
+const TYPE_RESPONSE: i32 = 0;
+const TYPE_COMMAND: i32 = 2;
+const TYPE_AUTH_RESPONSE: i32 = 2;
+const TYPE_AUTH: i32 = 3;
+
+/// The largest body a client may send, like the vanilla server.
+const MAX_REQUEST_BODY: usize = 1446;
+
+/// The largest body sent in a single response packet, longer responses are split up.
+const MAX_RESPONSE_BODY: usize = 4096;
+
+#[derive(Debug, Error)]
+pub enum RconError {
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+
+    #[error("invalid packet length {0}")]
+    InvalidLength(i32),
+}
+
+struct RconPacket {
+    id: i32,
+    kind: i32,
+    body: String,
+}
+
+impl RconPacket {
+    async fn read_from(stream: &mut TcpStream) -> Result<RconPacket, RconError> {
+        let length = stream.read_i32_le().await?;
+        if !(10..=(MAX_REQUEST_BODY + 10) as i32).contains(&length) {
+            return Err(RconError::InvalidLength(length));
+        }
+
+        let id = stream.read_i32_le().await?;
+        let kind = stream.read_i32_le().await?;
+
+        // The body and the empty string after it are both null terminated.
+        let mut body = vec![0; length as usize - 8];
+        stream.read_exact(&mut body).await?;
+        let end = body.iter().position(|&b| b == 0).unwrap_or(body.len());
+
+        Ok(RconPacket {
+            id,
+            kind,
+            body: String::from_utf8_lossy(&body[..end]).into_owned(),
+        })
+    }
+
+    async fn write_to(&self, stream: &mut TcpStream) -> Result<(), RconError> {
+        let mut out = Vec::with_capacity(self.body.len() + 14);
+        out.extend_from_slice(&(self.body.len() as i32 + 10).to_le_bytes());
+        out.extend_from_slice(&self.id.to_le_bytes());
+        out.extend_from_slice(&self.kind.to_le_bytes());
+        out.extend_from_slice(self.body.as_bytes());
+        out.extend_from_slice(&[0, 0]);
+
+        stream.write_all(&out).await?;
+        Ok(())
+    }
+}
+
+/// Accepts RCON connections until the server shuts down.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let (host, port) = {
+        let config = state.config.read().await;
+        (config.rcon.host.clone(), config.rcon.port)
+    };
+
+    let listener = match TcpListener::bind((host.as_str(), port)).await {
+        Ok(listener) => listener,
+        Err(err) => {
+            error!("failed to start rcon listener: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    info!("listening on {}:{} for rcon connections", host, port);
+
+    loop {
+        select! {
+            res = listener.accept() => match res {
+                Ok((stream, address)) => {
+                    let state = state.clone();
+                    tokio::spawn(async move {
+                        if let Err(err) = handle(stream, address, state).await {
+                            debug!("rcon connection from {} closed: {:#}", address, anyhow!(err));
+                        }
+                    });
+                }
+                Err(err) => error!("failed to accept rcon connection: {:#}", anyhow!(err)),
+            },
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting rcon listener");
+}
+
+async fn handle(
+    mut stream: TcpStream,
+    address: SocketAddr,
+    state: Arc<ServerState>,
+) -> Result<(), RconError> {
+    let mut authenticated = false;
+
+    loop {
+        let packet = RconPacket::read_from(&mut stream).await?;
+
+        match packet.kind {
+            TYPE_AUTH => {
+                // An empty password never matches, in case it was cleared by a reload. The
+                // comparison takes the same time no matter how much of the password is right.
+                authenticated = {
+                    let password = &state.config.read().await.rcon.password;
+                    !password.is_empty() && packet.body.as_bytes().ct_eq(password.as_bytes()).into()
+                };
+                if authenticated {
+                    info!("rcon client {} logged in", address);
+                } else {
+                    warn!("rcon client {} used the wrong password", address);
+                }
+
+                RconPacket {
+                    id: if authenticated { packet.id } else { -1 },
+                    kind: TYPE_AUTH_RESPONSE,
+                    body: String::new(),
+                }
+                .write_to(&mut stream)
+                .await?;
+            }
+            TYPE_COMMAND if authenticated => {
+                info!("rcon client {} issued command {}", address, packet.body);
+                let response = admin::execute(&state, &packet.body).await;
+
+                for body in split(&response) {
+                    RconPacket {
+                        id: packet.id,
+                        kind: TYPE_RESPONSE,
+                        body: body.to_string(),
+                    }
+                    .write_to(&mut stream)
+                    .await?;
+                }
+            }
+            // Clients send an empty response packet after a command, and wait for it to come
+            // back to know they've received every part of a long response.
+            TYPE_RESPONSE if authenticated => {
+                RconPacket {
+                    id: packet.id,
+                    kind: TYPE_RESPONSE,
+                    body: String::new(),
+                }
+                .write_to(&mut stream)
+                .await?;
+            }
+            _ => {
+                RconPacket {
+                    id: -1,
+                    kind: TYPE_AUTH_RESPONSE,
+                    body: String::new(),
+                }
+                .write_to(&mut stream)
+                .await?;
+            }
+        }
+    }
+}
+
+/// Splits a response into parts that fit in a packet, without splitting characters.
+fn split(response: &str) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut rest = response;
+
+    while rest.len() > MAX_RESPONSE_BODY {
+        let mut end = MAX_RESPONSE_BODY;
+        while !rest.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let (part, remaining) = rest.split_at(end);
+        parts.push(part);
+        rest = remaining;
+    }
+
+    parts.push(rest);
+    parts
+}
@@ -0,0 +1,173 @@
+use std::{sync::Arc, time::Duration};
+
+use anyhow::anyhow;
+use log::{debug, error, info, warn};
+use tokio::{select, time};
+use toml::Value;
+
+use crate::{
+    config::{self, Config, ConfigError},
+    shutdown::Shutdown,
+    state::ServerState,
+};
+
+/// How often the config file is checked for changes, when reloading on change is enabled.
+const WATCH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Settings that are only read on startup, so changing them has no effect until a restart.
+const RESTART_REQUIRED: &[&str] = &[
+    "server.host",
+    "server.port",
+    "server.log_level",
+    "server.reload_on_change",
+    "world.schematic",
+    "world.anvil",
+    "world.origin",
+    "world.min_chunk",
+    "world.max_chunk",
+    "world.block_report",
+    "transfer.enabled",
+    "transfer.check_interval",
+    "transfer.release_rate",
+    "queue.enabled",
+    "queue.release_rate",
+    "query",
+    "rcon.enabled",
+    "rcon.host",
+    "rcon.port",
+    "metrics",
+    "api.enabled",
+    "api.host",
+    "api.port",
+];
+
+/// Reads and validates the config file the same way as on startup, and swaps it in if it's
+/// valid. Returns the keys that changed, which are also logged.
+pub async fn reload(state: &ServerState) -> Result<Vec<String>, ConfigError> {
+    let _reloading = state.reloading.lock().await;
+
+    let (mut config, ignored) = config::load(&state.config_path, state.port_override)?;
+    config::warn_ignored(&ignored);
+
+    // This also loads the icon of the new config, so it's not read while holding the lock.
+    state.status.update_config(&mut config, &state.players);
+
+    let changes = {
+        let mut current = state.config.write().await;
+        let changes = diff(&current, &config)?;
+        *current = config;
+        changes
+    };
+
+    if changes.is_empty() {
+        info!("reloaded config file, nothing changed");
+    } else {
+        info!("reloaded config file, changed: {}", changes.join(", "));
+    }
+
+    for key in changes.iter().filter(|key| requires_restart(key)) {
+        warn!("{} was changed, but only takes effect after a restart", key);
+    }
+
+    Ok(changes)
+}
+
+/// Reloads the config file whenever the process receives SIGHUP.
+#[cfg(unix)]
+pub async fn hangup(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    use tokio::signal::unix::{signal, SignalKind};
+
+    let mut hangup = match signal(SignalKind::hangup()) {
+        Ok(hangup) => hangup,
+        Err(err) => {
+            error!("failed to listen for SIGHUP: {:#}", anyhow!(err));
+            return;
+        }
+    };
+
+    loop {
+        select! {
+            Some(_) = hangup.recv() => {
+                if let Err(err) = reload(&state).await {
+                    error!("failed to reload config file: {:#}", anyhow!(err));
+                }
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting config reloads on SIGHUP");
+}
+
+/// Reloads the config file whenever it's modified.
+pub async fn watch(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let modified = || {
+        std::fs::metadata(&state.config_path)
+            .and_then(|metadata| metadata.modified())
+            .ok()
+    };
+
+    let mut last_modified = modified();
+    let mut interval = time::interval(WATCH_INTERVAL);
+
+    loop {
+        select! {
+            _ = interval.tick() => {
+                let modified = modified();
+                if modified == last_modified {
+                    continue;
+                }
+
+                last_modified = modified;
+                if let Err(err) = reload(&state).await {
+                    error!("failed to reload config file: {:#}", anyhow!(err));
+                }
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting config file watcher");
+}
+
+fn requires_restart(key: &str) -> bool {
+    RESTART_REQUIRED.iter().any(|prefix| {
+        key == *prefix
+            || key
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+/// Lists the keys that differ between two configs, like `info.motd`.
+fn diff(old: &Config, new: &Config) -> Result<Vec<String>, ConfigError> {
+    fn walk(path: &str, old: Option<&Value>, new: Option<&Value>, changes: &mut Vec<String>) {
+        match (old, new) {
+            (Some(Value::Table(old)), Some(Value::Table(new))) => {
+                let mut keys = old.keys().chain(new.keys()).collect::<Vec<_>>();
+                keys.sort();
+                keys.dedup();
+
+                for key in keys {
+                    let path = if path.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{}.{}", path, key)
+                    };
+                    walk(&path, old.get(key), new.get(key), changes);
+                }
+            }
+            (old, new) if old != new => changes.push(path.to_string()),
+            _ => {}
+        }
+    }
+
+    let mut changes = Vec::new();
+    walk(
+        "",
+        Some(&Value::try_from(old)?),
+        Some(&Value::try_from(new)?),
+        &mut changes,
+    );
+    Ok(changes)
+}
@@ -0,0 +1,139 @@
+use protocol::{
+    chat::Message,
+    io::VarIntPrefixedVec,
+    packets::server::play::ServerPlayPacket,
+    scoreboard::{DisplaySlot, ObjectiveAction, ObjectiveKind, ScoreAction, TeamAction, TeamInfo},
+    VarInt,
+};
+
+const OBJECTIVE: &str = "limbo";
+
+/// The sidebar can't show more lines than this.
+pub const MAX_LINES: usize = 15;
+
+/// Keeps track of the sidebar a client is seeing, to only send what changed.
+///
+/// Every line is an invisible score entry inside its own team, with the text of the line as
+/// the team's prefix. Updating a prefix changes the line in place, while replacing the score
+/// entries would make the whole sidebar flicker.
+#[derive(Default)]
+pub struct Sidebar {
+    title: Option<String>,
+    lines: Vec<String>,
+}
+
+impl Sidebar {
+    pub fn update(&mut self, title: String, mut lines: Vec<String>) -> Vec<ServerPlayPacket> {
+        lines.truncate(MAX_LINES);
+
+        let mut packets = Vec::new();
+
+        match self.title {
+            None => {
+                packets.push(ServerPlayPacket::ScoreboardObjective {
+                    objective_name: OBJECTIVE.to_string(),
+                    action: ObjectiveAction::Create {
+                        display_name: Message::new(title.clone()),
+                        kind: ObjectiveKind::Integer,
+                    },
+                });
+                packets.push(ServerPlayPacket::DisplayScoreboard {
+                    position: DisplaySlot::Sidebar,
+                    score_name: OBJECTIVE.to_string(),
+                });
+            }
+            Some(ref old) if *old != title => {
+                packets.push(ServerPlayPacket::ScoreboardObjective {
+                    objective_name: OBJECTIVE.to_string(),
+                    action: ObjectiveAction::Update {
+                        display_name: Message::new(title.clone()),
+                        kind: ObjectiveKind::Integer,
+                    },
+                });
+            }
+            Some(_) => {}
+        }
+
+        // Lines are sorted by score, so the scores have to change when lines are added or removed.
+        let resized = lines.len() != self.lines.len();
+
+        for (i, line) in lines.iter().enumerate() {
+            match self.lines.get(i) {
+                Some(old) if old == line => {}
+                Some(_) => packets.push(ServerPlayPacket::Teams {
+                    team_name: team(i),
+                    action: TeamAction::UpdateInfo(TeamInfo::text(
+                        Message::new(line.clone()),
+                        Message::new(""),
+                    )),
+                }),
+                None => packets.push(ServerPlayPacket::Teams {
+                    team_name: team(i),
+                    action: TeamAction::Create {
+                        info: TeamInfo::text(Message::new(line.clone()), Message::new("")),
+                        entities: VarIntPrefixedVec(vec![entry(i)]),
+                    },
+                }),
+            }
+
+            if resized {
+                packets.push(ServerPlayPacket::UpdateScore {
+                    entity_name: entry(i),
+                    action: ScoreAction::Update {
+                        objective: OBJECTIVE.to_string(),
+                        value: VarInt((lines.len() - i) as i32),
+                    },
+                });
+            }
+        }
+
+        for i in lines.len()..self.lines.len() {
+            packets.push(ServerPlayPacket::UpdateScore {
+                entity_name: entry(i),
+                action: ScoreAction::Remove {
+                    objective: OBJECTIVE.to_string(),
+                },
+            });
+            packets.push(ServerPlayPacket::Teams {
+                team_name: team(i),
+                action: TeamAction::Remove,
+            });
+        }
+
+        self.title = Some(title);
+        self.lines = lines;
+
+        packets
+    }
+
+    pub fn clear(&mut self) -> Vec<ServerPlayPacket> {
+        if self.title.is_none() {
+            return Vec::new();
+        }
+
+        let mut packets = vec![ServerPlayPacket::ScoreboardObjective {
+            objective_name: OBJECTIVE.to_string(),
+            action: ObjectiveAction::Remove,
+        }];
+
+        for i in 0..self.lines.len() {
+            packets.push(ServerPlayPacket::Teams {
+                team_name: team(i),
+                action: TeamAction::Remove,
+            });
+        }
+
+        *self = Sidebar::default();
+
+        packets
+    }
+}
+
+fn team(line: usize) -> String {
+    format!("{}_{}", OBJECTIVE, line)
+}
+
+/// A unique entry name for a line that shows up as nothing, made out of formatting codes.
+fn entry(line: usize) -> String {
+    format!("§{:x}§r", line)
+}
@@ -0,0 +1,54 @@
+use std::{path::PathBuf, sync::atomic::AtomicBool};
+
+use tokio::sync::{broadcast, Mutex, Notify, RwLock};
+
+use crate::{
+    bans::BanList, config::Config, players::PlayerRegistry, queue::Queue, status::StatusCache,
+    tick::Tick, world::World,
+};
+
+/// Everything shared between connections and the server's background tasks.
+pub struct ServerState {
+    pub config_path: PathBuf,
+    /// The port given on the command line, which takes precedence over the config file.
+    pub port_override: Option<u16>,
+    pub config: RwLock<Config>,
+    /// Held for a whole reload, so that concurrent reloads can't leave the status cache built
+    /// from a different config than the one in use.
+    pub reloading: Mutex<()>,
+    pub world: World,
+    pub players: PlayerRegistry,
+    pub ticks: broadcast::Sender<Tick>,
+    pub backend_online: AtomicBool,
+    pub status: StatusCache,
+    pub queue: Queue,
+    pub bans: BanList,
+    pub stop: Notify,
+}
+
+impl ServerState {
+    pub fn new(
+        config_path: PathBuf,
+        port_override: Option<u16>,
+        mut config: Config,
+        world: World,
+        bans: BanList,
+    ) -> ServerState {
+        let status = StatusCache::new(&mut config);
+
+        ServerState {
+            config_path,
+            port_override,
+            config: RwLock::new(config),
+            reloading: Mutex::new(()),
+            world,
+            players: PlayerRegistry::default(),
+            ticks: broadcast::channel(16).0,
+            backend_online: AtomicBool::new(false),
+            status,
+            queue: Queue::default(),
+            bans,
+            stop: Notify::new(),
+        }
+    }
+}
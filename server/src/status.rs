@@ -0,0 +1,329 @@
+use std::{
+    borrow::Cow,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+};
+
+use anyhow::anyhow;
+use arc_swap::ArcSwap;
+use log::error;
+use once_cell::sync::Lazy;
+use protocol::{
+    chat::Message,
+    info::{PlayerSample, ServerInfo, ServerPlayerInfo, VersionInfo, VERSION},
+};
+use rand::Rng;
+use uuid::Uuid;
+
+use crate::{
+    config::{Config, Info, Rotation},
+    players::PlayerRegistry,
+    vhost,
+};
+
+/// The most players shown when hovering over the player count, the same as vanilla.
+const SAMPLE_SIZE: usize = 12;
+
+/// The protocol version sent to clients older than 1.7. They can't join anyway, so this makes
+/// them show the version name in red, like vanilla servers do.
+const LEGACY_PROTOCOL: i32 = 127;
+
+/// Sent if a response can't be rendered and there's no earlier one to fall back on.
+static FALLBACK: Lazy<String> = Lazy::new(|| {
+    serde_json::json!({
+        "version": VERSION,
+        "description": { "text": "" },
+    })
+    .to_string()
+});
+
+struct Template {
+    /// One response for every combination of MOTD and icon, grouped by MOTD, so that both can be
+    /// rotated independently.
+    variants: Vec<ServerInfo>,
+    icon_count: usize,
+    /// Clients older than 1.7 only get the version name and MOTD besides the player count.
+    legacy_version: String,
+    motds: Vec<String>,
+    rotation: Rotation,
+    hosts: Vec<String>,
+    hidden: bool,
+    max_players: isize,
+    hide_player_count: bool,
+    player_sample: bool,
+    hover: Vec<String>,
+}
+
+#[derive(Clone)]
+struct Rendered {
+    responses: Vec<String>,
+    icon_count: usize,
+    /// One legacy response for every MOTD.
+    legacy: Vec<String>,
+    rotation: Rotation,
+    hosts: Vec<String>,
+    hidden: bool,
+}
+
+/// The responses to status requests, rendered ahead of time so that answering a ping doesn't
+/// need the config or any lock. They're rendered again when the config or the players change.
+///
+/// This also keeps what's needed to pick the virtual host of a connection, for the same reason.
+pub struct StatusCache {
+    /// The template of the default host, followed by one for every virtual host.
+    templates: Mutex<Vec<Template>>,
+    rendered: ArcSwap<Vec<Rendered>>,
+    next: AtomicUsize,
+}
+
+impl StatusCache {
+    pub fn new(config: &mut Config) -> StatusCache {
+        let mut templates = templates(config);
+        let rendered = render(&mut templates, &PlayerRegistry::default(), &[]);
+
+        StatusCache {
+            templates: Mutex::new(templates),
+            rendered: ArcSwap::from_pointee(rendered),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Finds the virtual host for the address a client connected with.
+    pub fn find_vhost(&self, address: &str) -> Option<usize> {
+        let rendered = self.rendered.load();
+        vhost::find(
+            rendered[1..].iter().map(|rendered| &rendered.hosts[..]),
+            address,
+        )
+    }
+
+    /// Whether the given virtual host, or the default host if there's none, is hidden from the
+    /// server list.
+    pub fn hidden(&self, vhost: Option<usize>) -> bool {
+        host(&self.rendered.load(), vhost).hidden
+    }
+
+    /// Returns a response for the given virtual host, or for the default host if there's none.
+    pub fn response(&self, vhost: Option<usize>) -> String {
+        let rendered = self.rendered.load();
+        let rendered = host(&rendered, vhost);
+
+        let (motd, icon) = self.rotate(rendered);
+        rendered.responses[motd * rendered.icon_count + icon].clone()
+    }
+
+    /// Like [`StatusCache::response`], but for clients older than 1.7. Returns nothing if the
+    /// host is hidden.
+    pub fn legacy_response(&self, vhost: Option<usize>) -> Option<String> {
+        let rendered = self.rendered.load();
+        let rendered = host(&rendered, vhost);
+        if rendered.hidden {
+            return None;
+        }
+
+        let (motd, _) = self.rotate(rendered);
+        Some(rendered.legacy[motd].clone())
+    }
+
+    /// Picks the MOTD and icon to show next.
+    fn rotate(&self, rendered: &Rendered) -> (usize, usize) {
+        let motd_count = rendered.legacy.len();
+        match rendered.rotation {
+            Rotation::Sequential => {
+                let next = self.next.fetch_add(1, Ordering::Relaxed);
+                (next % motd_count, next % rendered.icon_count)
+            }
+            Rotation::Random => {
+                let mut rng = rand::thread_rng();
+                (
+                    rng.gen_range(0..motd_count),
+                    rng.gen_range(0..rendered.icon_count),
+                )
+            }
+        }
+    }
+
+    /// Renders the responses for a new config, which also loads its icons if they haven't been
+    /// yet.
+    pub fn update_config(&self, config: &mut Config, players: &PlayerRegistry) {
+        let mut templates = self.templates.lock().unwrap();
+        *templates = self::templates(config);
+        self.rendered.store(Arc::new(render(
+            &mut templates,
+            players,
+            &self.rendered.load(),
+        )));
+    }
+
+    // The players are read while holding the templates lock, so that concurrent updates can't
+    // leave an outdated player count behind.
+    pub fn update_players(&self, players: &PlayerRegistry) {
+        let mut templates = self.templates.lock().unwrap();
+        self.rendered.store(Arc::new(render(
+            &mut templates,
+            players,
+            &self.rendered.load(),
+        )));
+    }
+}
+
+fn host(rendered: &[Rendered], vhost: Option<usize>) -> &Rendered {
+    vhost
+        .and_then(|index| rendered.get(index + 1))
+        .unwrap_or(&rendered[0])
+}
+
+fn templates(config: &mut Config) -> Vec<Template> {
+    // The default host goes first, so virtual hosts that don't change the icon reuse its icon
+    // instead of loading it again.
+    let mut templates = vec![Template::new(&mut config.info, Vec::new())];
+    for vhost in &config.vhost {
+        templates.push(Template::new(
+            &mut config.info.for_vhost(vhost),
+            vhost.hosts.clone(),
+        ));
+    }
+
+    templates
+}
+
+/// Renders every template, keeping the previous responses of the ones that fail to render.
+fn render(
+    templates: &mut [Template],
+    players: &PlayerRegistry,
+    previous: &[Rendered],
+) -> Vec<Rendered> {
+    templates
+        .iter_mut()
+        .enumerate()
+        .map(|(i, template)| {
+            template.render(players).unwrap_or_else(|err| {
+                error!("failed to render status response: {:#}", anyhow!(err));
+                previous.get(i).cloned().unwrap_or_else(|| Rendered {
+                    responses: vec![FALLBACK.clone()],
+                    icon_count: 1,
+                    legacy: template
+                        .render_legacy(players)
+                        .into_iter()
+                        .take(1)
+                        .collect(),
+                    rotation: template.rotation,
+                    hosts: template.hosts.clone(),
+                    hidden: template.hidden,
+                })
+            })
+        })
+        .collect()
+}
+
+impl Template {
+    fn new(info: &mut Info, hosts: Vec<String>) -> Template {
+        let version = match (&info.version_name, info.version_protocol) {
+            (None, None) => VERSION,
+            (name, protocol) => VersionInfo {
+                name: name.clone().map_or(VERSION.name, Cow::Owned),
+                protocol: protocol.unwrap_or(VERSION.protocol),
+            },
+        };
+
+        let motds = info
+            .motds()
+            .into_iter()
+            .map(str::to_string)
+            .collect::<Vec<_>>();
+        let icons = match info.icons() {
+            [] => vec![None],
+            icons => icons.iter().map(|icon| Some(&icon[..])).collect(),
+        };
+
+        let variants = motds
+            .iter()
+            .flat_map(|motd| {
+                icons.iter().map(|&icon| {
+                    ServerInfo::new(version.clone(), None, Message::new(motd.clone()), icon)
+                })
+            })
+            .collect();
+
+        Template {
+            variants,
+            icon_count: icons.len(),
+            legacy_version: version.name.to_string(),
+            motds,
+            rotation: info.rotation,
+            hosts,
+            hidden: info.hidden,
+            max_players: info.max_players,
+            hide_player_count: info.hide_player_count,
+            player_sample: info.player_sample,
+            hover: info.hover.clone(),
+        }
+    }
+
+    fn render(&mut self, players: &PlayerRegistry) -> Result<Rendered, serde_json::Error> {
+        let online = players.len();
+        let sample = if !self.hover.is_empty() {
+            self.hover
+                .iter()
+                .map(|line| PlayerSample {
+                    name: line.clone(),
+                    id: Uuid::nil(),
+                })
+                .collect()
+        } else if self.player_sample {
+            players
+                .all()
+                .into_iter()
+                .take(SAMPLE_SIZE)
+                .map(|player| PlayerSample {
+                    name: player.name,
+                    id: player.uuid,
+                })
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        let mut responses = Vec::with_capacity(self.variants.len());
+        for info in &mut self.variants {
+            if !self.hide_player_count {
+                info.set_players(Some(
+                    ServerPlayerInfo::simple(online as isize, self.max_players)
+                        .with_sample(sample.clone()),
+                ));
+            }
+
+            responses.push(serde_json::to_string(info)?);
+        }
+
+        Ok(Rendered {
+            responses,
+            icon_count: self.icon_count,
+            legacy: self.render_legacy(players),
+            rotation: self.rotation,
+            hosts: self.hosts.clone(),
+            hidden: self.hidden,
+        })
+    }
+
+    fn render_legacy(&self, players: &PlayerRegistry) -> Vec<String> {
+        // Legacy clients always expect a player count.
+        let (online, max) = if self.hide_player_count {
+            (0, 0)
+        } else {
+            (players.len() as isize, self.max_players)
+        };
+
+        self.motds
+            .iter()
+            .map(|motd| {
+                format!(
+                    "§1\0{}\0{}\0{}\0{}\0{}",
+                    LEGACY_PROTOCOL, self.legacy_version, motd, online, max
+                )
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,52 @@
+use protocol::{
+    chat::Message,
+    io::{BooleanPrefixedOption, VarIntPrefixedVec},
+    packets::server::play::ServerPlayPacket,
+    player_info::{AddPlayerAction, PlayerInfo, UpdateLatencyAction},
+    types::GameMode,
+    VarInt,
+};
+use uuid::Uuid;
+
+use crate::players::PlayerHandle;
+
+pub fn add_players(players: &[PlayerHandle], game_mode: GameMode) -> ServerPlayPacket {
+    ServerPlayPacket::PlayerInfo {
+        info: PlayerInfo::AddPlayer(VarIntPrefixedVec(
+            players
+                .iter()
+                .map(|player| AddPlayerAction {
+                    uuid: player.uuid,
+                    name: player.name.clone(),
+                    properties: VarIntPrefixedVec(player.properties.clone()),
+                    game_mode,
+                    ping: VarInt(player.ping() as i32),
+                    display_name: BooleanPrefixedOption(None),
+                })
+                .collect(),
+        )),
+    }
+}
+
+pub fn remove_player(uuid: Uuid) -> ServerPlayPacket {
+    ServerPlayPacket::PlayerInfo {
+        info: PlayerInfo::RemovePlayer(VarIntPrefixedVec(vec![uuid])),
+    }
+}
+
+pub fn update_latency(player: &PlayerHandle) -> ServerPlayPacket {
+    ServerPlayPacket::PlayerInfo {
+        info: PlayerInfo::UpdateLatency(VarIntPrefixedVec(vec![UpdateLatencyAction {
+            uuid: player.uuid,
+            ping: VarInt(player.ping() as i32),
+        }])),
+    }
+}
+
+pub fn header_and_footer(header: Option<String>, footer: Option<String>) -> ServerPlayPacket {
+    // An empty component hides the header or footer.
+    ServerPlayPacket::PlayerListHeaderAndFooter {
+        header: Message::new(header.unwrap_or_default()),
+        footer: Message::new(footer.unwrap_or_default()),
+    }
+}
@@ -0,0 +1,79 @@
+use std::{
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use log::debug;
+use protocol::packets::server::play::ServerPlayPacket;
+use tokio::{select, sync::broadcast, time};
+
+use crate::shutdown::Shutdown;
+
+pub const TICKS_PER_SECOND: u64 = 20;
+const TICK_DURATION: Duration = Duration::from_millis(1000 / TICKS_PER_SECOND);
+const STATS_INTERVAL: u64 = 30 * TICKS_PER_SECOND;
+
+const DAY_LENGTH: i64 = 24000;
+
+static CURRENT_TICK: AtomicU64 = AtomicU64::new(0);
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tick(pub u64);
+
+impl Tick {
+    pub fn current() -> Tick {
+        Tick(CURRENT_TICK.load(Ordering::Relaxed))
+    }
+
+    pub fn every(self, ticks: u64) -> bool {
+        self.0.is_multiple_of(ticks)
+    }
+
+    /// Builds the time packet for this tick. A locked time is sent as a negative value, which
+    /// stops the client from advancing the daylight cycle by itself.
+    pub fn time_update(self, locked_time: Option<i64>) -> ServerPlayPacket {
+        let time_of_day = match locked_time {
+            Some(time) => match time.rem_euclid(DAY_LENGTH) {
+                0 => -DAY_LENGTH,
+                time => -time,
+            },
+            None => self.0 as i64 % DAY_LENGTH,
+        };
+
+        ServerPlayPacket::TimeUpdate {
+            world_age: self.0 as i64,
+            time_of_day,
+        }
+    }
+}
+
+/// Broadcasts a tick to every client at a fixed rate, until the server shuts down.
+pub async fn run(ticks: broadcast::Sender<Tick>, mut shutdown: Shutdown) {
+    let mut interval = time::interval(TICK_DURATION);
+    let mut stats_start = Instant::now();
+
+    loop {
+        select! {
+            _ = interval.tick() => {
+                let tick = Tick(CURRENT_TICK.fetch_add(1, Ordering::Relaxed) + 1);
+
+                // This will fail if no clients are connected, but we don't care.
+                let _ = ticks.send(tick);
+
+                // Clients handle ticks on their own tasks, so only the rate ticks are sent at is
+                // known here.
+                if tick.every(STATS_INTERVAL) {
+                    debug!(
+                        "ticking at {:.2} tps",
+                        STATS_INTERVAL as f64 / stats_start.elapsed().as_secs_f64()
+                    );
+
+                    stats_start = Instant::now();
+                }
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting tick loop");
+}
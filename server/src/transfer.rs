@@ -0,0 +1,205 @@
+use std::{
+    collections::HashMap,
+    io::Cursor,
+    sync::{atomic::Ordering, Arc},
+    time::{Duration, Instant},
+};
+
+use anyhow::anyhow;
+use log::{debug, info, warn};
+use protocol::{
+    info::VERSION,
+    packets::{
+        client::{handshake::ClientHandshakePacket, status::ClientStatusPacket},
+        State,
+    },
+    Packet, PacketField, VarInt,
+};
+use thiserror::Error;
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::TcpStream,
+    select, time,
+};
+use uuid::Uuid;
+
+use crate::{client::ClientEvent, shutdown::Shutdown, state::ServerState};
+
+const PING_TIMEOUT: Duration = Duration::from_secs(3);
+
+#[derive(Debug, Error)]
+pub enum PingError {
+    #[error("timed out")]
+    Timeout,
+
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+
+    #[error("encoding error")]
+    Encode(#[from] protocol::WriteError),
+
+    #[error("decoding error")]
+    Decode(#[from] protocol::FieldReadError),
+
+    #[error("unexpected packet with id {0}")]
+    UnexpectedPacket(i32),
+}
+
+/// Checks whether a server is up by requesting its status, like the server list does.
+pub async fn ping(host: &str, port: u16) -> Result<(), PingError> {
+    time::timeout(PING_TIMEOUT, request_status(host, port))
+        .await
+        .map_err(|_| PingError::Timeout)?
+}
+
+async fn request_status(host: &str, port: u16) -> Result<(), PingError> {
+    let mut stream = TcpStream::connect((host, port)).await?;
+
+    let mut out = Vec::new();
+    for packet in [
+        ClientHandshakePacket::Handshake {
+            protocol_version: VarInt(VERSION.protocol as i32),
+            server_address: host.to_string(),
+            server_port: port,
+            next_state: State::Status,
+        }
+        .write_to_frame()?,
+        ClientStatusPacket::Request {}.write_to_frame()?,
+    ] {
+        out.extend_from_slice(&packet);
+    }
+
+    stream.write_all(&out).await?;
+
+    let mut buffer = Vec::new();
+    loop {
+        if stream.read_buf(&mut buffer).await? == 0 {
+            return Err(PingError::Io(std::io::ErrorKind::UnexpectedEof.into()));
+        }
+
+        let mut cursor = Cursor::new(&buffer[..]);
+        let length = match VarInt::read_from(&mut cursor) {
+            Ok(length) => length.0 as usize,
+            Err(_) => continue,
+        };
+
+        if buffer.len() < cursor.position() as usize + length {
+            continue;
+        }
+
+        let id = VarInt::read_from(&mut cursor)?.0;
+        if id != 0 {
+            return Err(PingError::UnexpectedPacket(id));
+        }
+
+        let response = String::read_from(&mut cursor)?;
+        debug!("backend status: {}", response);
+        return Ok(());
+    }
+}
+
+trait WriteToFrame {
+    fn write_to_frame(&self) -> Result<Vec<u8>, protocol::WriteError>;
+}
+
+impl<P: Packet> WriteToFrame for P {
+    fn write_to_frame(&self) -> Result<Vec<u8>, protocol::WriteError> {
+        let mut packet = Vec::new();
+        self.write_to(&mut packet)?;
+
+        let mut frame = VarInt(packet.len() as i32)
+            .write_to_vec()
+            .map_err(protocol::WriteError::WritePacketId)?;
+        frame.extend_from_slice(&packet);
+        Ok(frame)
+    }
+}
+
+struct Attempt {
+    count: u32,
+    last: Instant,
+}
+
+/// Keeps track of whether the backend server is up, and sends players back to it through the
+/// proxy once it is, a few at a time.
+pub async fn run(state: Arc<ServerState>, mut shutdown: Shutdown) {
+    let (check_interval, release_interval) = {
+        let config = state.config.read().await;
+        (
+            Duration::from_secs(config.transfer.check_interval.max(1)),
+            Duration::from_secs_f64(1.0 / config.transfer.release_rate.max(0.01)),
+        )
+    };
+
+    let mut check = time::interval(check_interval);
+    let mut release = time::interval(release_interval);
+    let mut attempts = HashMap::<Uuid, Attempt>::new();
+
+    loop {
+        let online = state.backend_online.load(Ordering::Relaxed);
+
+        select! {
+            _ = check.tick() => {
+                let (host, port) = {
+                    let config = state.config.read().await;
+                    (config.transfer.host.clone(), config.transfer.port)
+                };
+
+                let now_online = match ping(&host, port).await {
+                    Ok(()) => true,
+                    Err(err) => {
+                        debug!("backend status check failed: {:#}", anyhow!(err));
+                        false
+                    }
+                };
+
+                if now_online != online {
+                    if now_online {
+                        info!("backend is back online, transferring {} players", state.players.len());
+                    } else {
+                        warn!("backend went offline");
+                    }
+
+                    state.backend_online.store(now_online, Ordering::Relaxed);
+                    attempts.clear();
+                }
+
+                attempts.retain(|uuid, _| state.players.get(uuid).is_some());
+            }
+            _ = release.tick(), if online => {
+                let config = state.config.read().await;
+                let retry_interval = Duration::from_secs(config.transfer.retry_interval);
+
+                let next = state.players.all().into_iter().find(|player| {
+                    match attempts.get(&player.uuid) {
+                        Some(attempt) => {
+                            attempt.count <= config.transfer.max_retries
+                                && attempt.last.elapsed() >= retry_interval
+                        }
+                        None => true,
+                    }
+                });
+
+                if let Some(player) = next {
+                    let attempt = attempts.entry(player.uuid).or_insert(Attempt {
+                        count: 0,
+                        last: Instant::now(),
+                    });
+                    attempt.count += 1;
+                    attempt.last = Instant::now();
+
+                    if attempt.count > config.transfer.max_retries {
+                        warn!("last attempt to transfer {}, giving up after this", player.name);
+                    } else {
+                        debug!("transferring {} (attempt {})", player.name, attempt.count);
+                    }
+
+                    player.send(ClientEvent::Transfer(config.transfer.server.clone()));
+                }
+            }
+            _ = shutdown.recv() => break
+        }
+    }
+
+    debug!("halting backend checks");
+}
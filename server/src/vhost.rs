@@ -0,0 +1,52 @@
+use crate::{config::VirtualHost, world::Spawn};
+
+/// Finds the virtual host for the address a client connected with, the first one with a
+/// matching hostname.
+pub fn find<'a, I: IntoIterator<Item = &'a [String]>>(vhosts: I, address: &str) -> Option<usize> {
+    let host = normalize(address);
+    vhosts.into_iter().position(|hosts| {
+        hosts
+            .iter()
+            .any(|pattern| matches(&pattern.to_lowercase(), &host))
+    })
+}
+
+/// Strips what mods and proxies append to the address, like `\0FML2\0` from Forge, the forwarded
+/// data from BungeeCord or `///` and the rest from TCPShield.
+fn normalize(address: &str) -> String {
+    let host = address.split('\0').next().unwrap_or_default();
+    let host = host.split("///").next().unwrap_or_default();
+    host.trim_end_matches('.').to_lowercase()
+}
+
+/// Matches a hostname against a pattern, where `*` matches any number of characters.
+fn matches(pattern: &str, host: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == host,
+        Some((prefix, rest)) => {
+            let host = match host.strip_prefix(prefix) {
+                Some(host) => host,
+                None => return false,
+            };
+
+            // Try every possible length for the part matched by the wildcard.
+            host.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(host.len()))
+                .any(|i| matches(rest, &host[i..]))
+        }
+    }
+}
+
+/// Where players connecting through a virtual host spawn.
+pub fn spawn(vhost: &VirtualHost, default: &Spawn) -> Spawn {
+    let mut spawn = match vhost.spawn {
+        Some(origin) => Spawn::at(origin),
+        None => default.clone(),
+    };
+    if let Some(angle) = vhost.spawn_angle {
+        spawn.angle = angle;
+    }
+
+    spawn
+}
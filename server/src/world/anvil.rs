@@ -0,0 +1,269 @@
+use std::{
+    fs::File,
+    io::{Read, Seek, SeekFrom},
+    path::Path,
+};
+
+use anyhow::anyhow;
+use flate2::read::{GzDecoder, ZlibDecoder};
+use log::{debug, warn};
+use protocol::{
+    chunk::{self, ChunkSection, SECTION_VOLUME, SECTION_WIDTH},
+    nbt::Nbt,
+    types::Position,
+    PacketField,
+};
+
+use super::{
+    blocks::{self, BlockRegistry},
+    Spawn, World, WorldError, BIOME, HEIGHT, MIN_Y,
+};
+
+const REGION_WIDTH: i32 = 32;
+const SECTOR_SIZE: u64 = 4096;
+
+/// The first data version using the 1.18 chunk format, without the `Level` compound.
+const MIN_DATA_VERSION: i64 = 2844;
+
+/// Loads the chunks of a vanilla world folder within the inclusive chunk bounds, returning the
+/// spawn point stored in `level.dat`.
+pub fn load(
+    path: &Path,
+    blocks: &BlockRegistry,
+    min_chunk: [i32; 2],
+    max_chunk: [i32; 2],
+    world: &mut World,
+) -> Result<Option<Spawn>, WorldError> {
+    let mut discarded = 0;
+
+    for region_x in min_chunk[0].div_euclid(REGION_WIDTH)..=max_chunk[0].div_euclid(REGION_WIDTH) {
+        for region_z in
+            min_chunk[1].div_euclid(REGION_WIDTH)..=max_chunk[1].div_euclid(REGION_WIDTH)
+        {
+            let region_path = path
+                .join("region")
+                .join(format!("r.{}.{}.mca", region_x, region_z));
+
+            if !region_path.exists() {
+                debug!("skipping missing region file {}", region_path.display());
+                continue;
+            }
+
+            let mut region = File::open(&region_path)?;
+            let mut header = [0u8; SECTOR_SIZE as usize];
+            region.read_exact(&mut header)?;
+
+            for i in 0..(REGION_WIDTH * REGION_WIDTH) as usize {
+                let chunk_x = region_x * REGION_WIDTH + i as i32 % REGION_WIDTH;
+                let chunk_z = region_z * REGION_WIDTH + i as i32 / REGION_WIDTH;
+                if !(min_chunk[0]..=max_chunk[0]).contains(&chunk_x)
+                    || !(min_chunk[1]..=max_chunk[1]).contains(&chunk_z)
+                {
+                    continue;
+                }
+
+                let location = &header[i * 4..i * 4 + 4];
+                let offset = u32::from_be_bytes([0, location[0], location[1], location[2]]);
+                if offset == 0 {
+                    continue;
+                }
+
+                let sectors = location[3] as u64;
+                let chunk = read_chunk(&mut region, offset as u64 * SECTOR_SIZE, sectors)
+                    .and_then(|chunk| load_chunk(&chunk, chunk_x, chunk_z, blocks, world));
+                match chunk {
+                    Ok(count) => discarded += count,
+                    Err(err) => warn!(
+                        "failed to load chunk {}, {}: {:#}",
+                        chunk_x,
+                        chunk_z,
+                        anyhow!(err)
+                    ),
+                }
+            }
+        }
+    }
+
+    // 1.18 worlds reach from Y -64 to 319, but the limbo dimension is only 256 blocks tall.
+    if discarded > 0 {
+        warn!(
+            "discarded {} chunk sections outside of Y {} to {}, blocks there won't be shown",
+            discarded,
+            MIN_Y,
+            MIN_Y + HEIGHT as i32 - 1
+        );
+    }
+
+    let level_path = path.join("level.dat");
+    if !level_path.exists() {
+        warn!("world has no level.dat, using the default spawn point");
+        return Ok(None);
+    }
+
+    let level = super::read_nbt_file(&level_path)?;
+    let data = level
+        .get("Data")
+        .ok_or(WorldError::InvalidChunk("missing level data"))?;
+
+    let coordinate = |key| data.get(key).and_then(Nbt::as_i64).map(|v| v as i32);
+    Ok(
+        match (
+            coordinate("SpawnX"),
+            coordinate("SpawnY"),
+            coordinate("SpawnZ"),
+        ) {
+            (Some(x), Some(y), Some(z)) => Some(Spawn {
+                position: Position::new(x, y, z),
+                angle: data.get("SpawnAngle").and_then(Nbt::as_f64).unwrap_or(0.0) as f32,
+            }),
+            _ => None,
+        },
+    )
+}
+
+fn read_chunk(region: &mut File, offset: u64, sectors: u64) -> Result<Nbt, WorldError> {
+    region.seek(SeekFrom::Start(offset))?;
+
+    let mut header = [0u8; 5];
+    region.read_exact(&mut header)?;
+    let length = u32::from_be_bytes([header[0], header[1], header[2], header[3]]) as usize;
+
+    // The length is checked before allocating, so that a corrupt header can't ask for gigabytes.
+    if length as u64 > (sectors * SECTOR_SIZE).saturating_sub(4) {
+        return Err(WorldError::InvalidChunk("chunk is larger than its sectors"));
+    }
+
+    let mut data = vec![0; length.saturating_sub(1)];
+    region.read_exact(&mut data)?;
+
+    let mut decompressed = Vec::new();
+    match header[4] {
+        1 => GzDecoder::new(&data[..]).read_to_end(&mut decompressed)?,
+        2 => ZlibDecoder::new(&data[..]).read_to_end(&mut decompressed)?,
+        3 => {
+            decompressed = data;
+            0
+        }
+        _ => return Err(WorldError::InvalidChunk("unsupported compression")),
+    };
+
+    Ok(Nbt::read_from(&mut &decompressed[..])?)
+}
+
+fn load_chunk(
+    chunk: &Nbt,
+    chunk_x: i32,
+    chunk_z: i32,
+    blocks: &BlockRegistry,
+    world: &mut World,
+) -> Result<usize, WorldError> {
+    let data_version = chunk.get("DataVersion").and_then(Nbt::as_i64).unwrap_or(0);
+    if data_version < MIN_DATA_VERSION {
+        return Err(WorldError::InvalidChunk(
+            "chunk is older than 1.18, open the world in a 1.18 server to upgrade it",
+        ));
+    }
+
+    // Chunks that are still being generated can be missing parts of their terrain.
+    match chunk.get("Status").and_then(Nbt::as_str) {
+        Some("full") | Some("minecraft:full") => {}
+        _ => return Ok(0),
+    }
+
+    let sections = chunk
+        .get("sections")
+        .and_then(Nbt::as_list)
+        .ok_or(WorldError::InvalidChunk("missing sections"))?;
+
+    let mut discarded = 0;
+    for section in sections {
+        let y = match section.get("Y").and_then(Nbt::as_i64) {
+            Some(y) => y as i32,
+            None => continue,
+        };
+
+        let block_states = match section.get("block_states") {
+            Some(block_states) => block_states,
+            None => continue,
+        };
+
+        let palette = block_states
+            .get("palette")
+            .and_then(Nbt::as_list)
+            .ok_or(WorldError::InvalidChunk("missing block palette"))?
+            .iter()
+            .map(|state| palette_entry(state, blocks))
+            .collect::<Vec<_>>();
+
+        let indices = match block_states.get("data").and_then(Nbt::as_long_array) {
+            Some(data) => {
+                let data = data.iter().map(|&long| long as u64).collect::<Vec<_>>();
+                let bits = chunk::bits_needed(palette.len()).max(4);
+                chunk::unpack(&data, bits, SECTION_VOLUME)
+            }
+            None => vec![0; SECTION_VOLUME],
+        };
+
+        let mut section = ChunkSection::new(BIOME);
+        for (i, index) in indices.into_iter().enumerate() {
+            let state = palette.get(index as usize).copied().unwrap_or(0);
+            section.set(
+                i % SECTION_WIDTH,
+                i / (SECTION_WIDTH * SECTION_WIDTH),
+                (i / SECTION_WIDTH) % SECTION_WIDTH,
+                state,
+            );
+        }
+
+        if !section.is_empty() && !world.insert_section(chunk_x, chunk_z, y, section) {
+            discarded += 1;
+        }
+    }
+
+    let block_entities = chunk
+        .get("block_entities")
+        .and_then(Nbt::as_list)
+        .unwrap_or(&[]);
+
+    for block_entity in block_entities {
+        let mut data = match block_entity.as_compound() {
+            Some(data) => data.clone(),
+            None => continue,
+        };
+
+        let kind = data
+            .get("id")
+            .and_then(Nbt::as_str)
+            .and_then(blocks::block_entity_kind);
+        let position = (
+            data.get("x").and_then(Nbt::as_i64),
+            data.get("y").and_then(Nbt::as_i64),
+            data.get("z").and_then(Nbt::as_i64),
+        );
+
+        if let (Some(kind), (Some(x), Some(y), Some(z))) = (kind, position) {
+            for key in ["id", "x", "y", "z", "keepPacked"] {
+                data.remove(key);
+            }
+
+            world.add_block_entity(x as i32, y as i32, z as i32, kind, data);
+        }
+    }
+
+    Ok(discarded)
+}
+
+fn palette_entry(state: &Nbt, blocks: &BlockRegistry) -> u16 {
+    let name = state.get("Name").and_then(Nbt::as_str).unwrap_or_default();
+    let properties = state
+        .get("Properties")
+        .and_then(Nbt::as_compound)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| value.as_str().map(|value| (key.as_str(), value)));
+
+    blocks.get(name, properties).unwrap_or_else(|| {
+        warn!("unknown block state {} in world, replacing with air", name);
+        0
+    })
+}
@@ -0,0 +1,143 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use serde::Deserialize;
+
+use super::WorldError;
+
+/// Block entity type names in 1.18.1 registry order, so their index is their network ID.
+const BLOCK_ENTITY_KINDS: &[&str] = &[
+    "furnace",
+    "chest",
+    "trapped_chest",
+    "ender_chest",
+    "jukebox",
+    "dispenser",
+    "dropper",
+    "sign",
+    "mob_spawner",
+    "piston",
+    "brewing_stand",
+    "enchanting_table",
+    "end_portal",
+    "beacon",
+    "skull",
+    "daylight_detector",
+    "hopper",
+    "comparator",
+    "banner",
+    "structure_block",
+    "end_gateway",
+    "command_block",
+    "shulker_box",
+    "bed",
+    "conduit",
+    "barrel",
+    "smoker",
+    "blast_furnace",
+    "lectern",
+    "bell",
+    "jigsaw",
+    "campfire",
+    "beehive",
+    "sculk_sensor",
+];
+
+pub fn block_entity_kind(id: &str) -> Option<i32> {
+    let id = id.to_lowercase();
+    let name = id.strip_prefix("minecraft:").unwrap_or(&id);
+    BLOCK_ENTITY_KINDS
+        .iter()
+        .position(|&kind| kind == name)
+        .map(|i| i as i32)
+}
+
+#[derive(Deserialize)]
+struct ReportBlock {
+    states: Vec<ReportState>,
+}
+
+#[derive(Deserialize)]
+struct ReportState {
+    id: u16,
+    #[serde(default)]
+    properties: BTreeMap<String, String>,
+    #[serde(default)]
+    default: bool,
+}
+
+/// Maps block states to their global palette IDs, using the `blocks.json` report generated by the
+/// vanilla server (`java -DbundlerMainClass=net.minecraft.data.Main -jar server.jar --reports`).
+pub struct BlockRegistry {
+    states: HashMap<String, u16>,
+    defaults: HashMap<String, BTreeMap<String, String>>,
+}
+
+impl BlockRegistry {
+    pub fn load(path: &Path) -> Result<BlockRegistry, WorldError> {
+        let report: HashMap<String, ReportBlock> = serde_json::from_slice(&std::fs::read(path)?)?;
+
+        let mut states = HashMap::new();
+        let mut defaults = HashMap::new();
+
+        for (name, block) in report {
+            for state in block.states {
+                states.insert(state_key(&name, &state.properties), state.id);
+                if state.default {
+                    defaults.insert(name.clone(), state.properties);
+                }
+            }
+        }
+
+        Ok(BlockRegistry { states, defaults })
+    }
+
+    /// Looks up a state written as `minecraft:oak_stairs[facing=north,half=top]`. Properties that
+    /// are left out take the value of the default state of the block.
+    pub fn parse(&self, state: &str) -> Option<u16> {
+        let (name, properties) = match state.split_once('[') {
+            Some((name, properties)) => (name, properties.trim_end_matches(']')),
+            None => (state, ""),
+        };
+
+        self.get(
+            name,
+            properties
+                .split(',')
+                .filter_map(|property| property.split_once('=')),
+        )
+    }
+
+    pub fn get<'a, I>(&self, name: &str, properties: I) -> Option<u16>
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+    {
+        let name = if name.contains(':') {
+            name.to_string()
+        } else {
+            format!("minecraft:{}", name)
+        };
+
+        let mut state = self.defaults.get(&name)?.clone();
+        for (key, value) in properties {
+            state.insert(key.trim().to_string(), value.trim().to_string());
+        }
+
+        self.states.get(&state_key(&name, &state)).copied()
+    }
+}
+
+fn state_key(name: &str, properties: &BTreeMap<String, String>) -> String {
+    if properties.is_empty() {
+        return name.to_string();
+    }
+
+    let properties = properties
+        .iter()
+        .map(|(key, value)| format!("{}={}", key, value))
+        .collect::<Vec<_>>();
+
+    format!("{}[{}]", name, properties.join(","))
+}
@@ -0,0 +1,294 @@
+use std::{collections::HashMap, io::Read, path::Path};
+
+use anyhow::anyhow;
+use flate2::read::GzDecoder;
+use log::{info, warn};
+use once_cell::sync::OnceCell;
+use protocol::{
+    chunk::{self, BlockEntity, ChunkSection, LightData, SECTION_WIDTH},
+    io::VarIntPrefixedVec,
+    nbt::{Compound, Nbt},
+    packets::server::play::ServerPlayPacket,
+    types::Position,
+    PacketField, VarInt,
+};
+use thiserror::Error;
+
+use crate::config;
+
+use self::blocks::BlockRegistry;
+
+mod anvil;
+mod blocks;
+mod schematic;
+
+// These should match the dimension type sent in `dimension.nbt`.
+const MIN_Y: i32 = 0;
+const HEIGHT: usize = 256;
+const SECTION_COUNT: usize = HEIGHT / SECTION_WIDTH;
+
+/// The ID of `minecraft:plains` in the biome registry sent in `dimension_codec.nbt`.
+const BIOME: VarInt = VarInt(1);
+
+#[derive(Debug, Error)]
+pub enum WorldError {
+    #[error("io error")]
+    Io(#[from] std::io::Error),
+
+    #[error("nbt decoding error")]
+    Nbt(#[from] protocol::FieldReadError),
+
+    #[error("block report decoding error")]
+    BlockReport(#[from] serde_json::Error),
+
+    #[error("failed to load block report {path}, it has to be generated with the vanilla server")]
+    MissingBlockReport {
+        path: String,
+        #[source]
+        source: Box<WorldError>,
+    },
+
+    #[error("invalid schematic: {0}")]
+    InvalidSchematic(&'static str),
+
+    #[error("invalid world: {0}")]
+    InvalidChunk(&'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct Spawn {
+    pub position: Position,
+    pub angle: f32,
+}
+
+impl Spawn {
+    pub fn at(origin: [i32; 3]) -> Spawn {
+        Spawn {
+            position: Position::new(origin[0], origin[1], origin[2]),
+            angle: 0.0,
+        }
+    }
+
+    /// The location players are placed at, in the center of the spawn block.
+    pub fn location(&self) -> Location {
+        Location {
+            x: self.position.x as f64 + 0.5,
+            y: self.position.y as f64,
+            z: self.position.z as f64 + 0.5,
+            yaw: self.angle,
+            pitch: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Location {
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+    pub yaw: f32,
+    pub pitch: f32,
+}
+
+impl Location {
+    pub fn horizontal_distance(&self, other: &Location) -> f64 {
+        ((self.x - other.x).powi(2) + (self.z - other.z).powi(2)).sqrt()
+    }
+}
+
+pub struct World {
+    chunks: HashMap<(i32, i32), Chunk>,
+    pub spawn: Spawn,
+}
+
+impl Default for World {
+    fn default() -> World {
+        World {
+            chunks: HashMap::new(),
+            spawn: Spawn::at(config::World::default().origin),
+        }
+    }
+}
+
+impl World {
+    pub fn load(config: &config::World) -> Result<World, WorldError> {
+        let mut world = World {
+            spawn: Spawn::at(config.origin),
+            ..World::default()
+        };
+
+        if config.anvil.is_none() && config.schematic.is_none() {
+            return Ok(world);
+        }
+
+        let blocks = BlockRegistry::load(Path::new(&config.block_report)).map_err(|err| {
+            WorldError::MissingBlockReport {
+                path: config.block_report.clone(),
+                source: Box::new(err),
+            }
+        })?;
+
+        if let Some(ref path) = config.anvil {
+            let spawn = anvil::load(
+                Path::new(path),
+                &blocks,
+                config.min_chunk,
+                config.max_chunk,
+                &mut world,
+            )?;
+
+            if let Some(spawn) = spawn {
+                world.spawn = spawn;
+            }
+
+            info!("loaded world {} ({} chunks)", path, world.chunks.len());
+        }
+
+        if let Some(ref path) = config.schematic {
+            schematic::load(Path::new(path), &blocks, config.origin, &mut world)?;
+            info!("loaded schematic {} ({} chunks)", path, world.chunks.len());
+        }
+
+        Ok(world)
+    }
+
+    pub fn set_block(&mut self, x: i32, y: i32, z: i32, state: u16) {
+        let section = match section_index(y) {
+            Some(section) => section,
+            None => return,
+        };
+
+        self.chunk_mut(x, z).sections[section].set(
+            x.rem_euclid(16) as usize,
+            (y - MIN_Y).rem_euclid(16) as usize,
+            z.rem_euclid(16) as usize,
+            state,
+        );
+    }
+
+    /// Replaces a section of a chunk, returning false if it lies outside of the world's height.
+    pub fn insert_section(
+        &mut self,
+        chunk_x: i32,
+        chunk_z: i32,
+        section_y: i32,
+        section: ChunkSection,
+    ) -> bool {
+        match section_index(section_y * 16) {
+            Some(index) => {
+                self.chunks
+                    .entry((chunk_x, chunk_z))
+                    .or_insert_with(Chunk::new)
+                    .sections[index] = section;
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn add_block_entity(&mut self, x: i32, y: i32, z: i32, kind: i32, data: Compound) {
+        if section_index(y).is_none() {
+            return;
+        }
+
+        self.chunk_mut(x, z).block_entities.push(BlockEntity {
+            packed_xz: ((x.rem_euclid(16) << 4) | z.rem_euclid(16)) as u8,
+            y: y as i16,
+            kind: VarInt(kind),
+            data: Nbt::Compound(data),
+        });
+    }
+
+    fn chunk_mut(&mut self, x: i32, z: i32) -> &mut Chunk {
+        self.chunks
+            .entry((x.div_euclid(16), z.div_euclid(16)))
+            .or_insert_with(Chunk::new)
+    }
+
+    pub fn chunk_packets(&self) -> impl Iterator<Item = ServerPlayPacket> + '_ {
+        self.chunks
+            .iter()
+            .map(|(&(x, z), chunk)| chunk.packet(x, z))
+    }
+}
+
+fn section_index(y: i32) -> Option<usize> {
+    let section = (y - MIN_Y).div_euclid(16);
+    if (0..SECTION_COUNT as i32).contains(&section) {
+        Some(section as usize)
+    } else {
+        None
+    }
+}
+
+struct Chunk {
+    sections: Vec<ChunkSection>,
+    block_entities: Vec<BlockEntity>,
+    encoded: OnceCell<(Nbt, Vec<u8>)>,
+}
+
+impl Chunk {
+    fn new() -> Chunk {
+        Chunk {
+            sections: vec![ChunkSection::new(BIOME); SECTION_COUNT],
+            block_entities: Vec::new(),
+            encoded: OnceCell::new(),
+        }
+    }
+
+    fn packet(&self, x: i32, z: i32) -> ServerPlayPacket {
+        // Chunks never change after loading, so they only need to be encoded once.
+        let (heightmaps, data) = self.encoded.get_or_init(|| {
+            let mut data = Vec::new();
+            for section in &self.sections {
+                if let Err(err) = section.write_to(&mut data) {
+                    warn!("failed to encode chunk section: {:#}", anyhow!(err));
+                }
+            }
+
+            (self.heightmaps(), data)
+        });
+
+        ServerPlayPacket::ChunkData {
+            chunk_x: x,
+            chunk_z: z,
+            heightmaps: heightmaps.clone(),
+            data: VarIntPrefixedVec(data.clone()),
+            block_entities: VarIntPrefixedVec(self.block_entities.clone()),
+            light: LightData::full_bright(SECTION_COUNT),
+        }
+    }
+
+    fn heightmaps(&self) -> Nbt {
+        let mut heights = vec![0u64; SECTION_WIDTH * SECTION_WIDTH];
+        for (i, height) in heights.iter_mut().enumerate() {
+            let (x, z) = (i % SECTION_WIDTH, i / SECTION_WIDTH);
+            *height = (0..HEIGHT)
+                .rev()
+                .find(|&y| self.sections[y / 16].get(x, y % 16, z) != 0)
+                .map(|y| y as u64 + 1)
+                .unwrap_or(0);
+        }
+
+        let packed = chunk::pack(&heights, chunk::bits_needed(HEIGHT + 1));
+        let packed = Nbt::LongArray(packed.into_iter().map(|long| long as i64).collect());
+
+        let mut heightmaps = Compound::new();
+        heightmaps.insert("MOTION_BLOCKING".to_string(), packed.clone());
+        heightmaps.insert("WORLD_SURFACE".to_string(), packed);
+        Nbt::Compound(heightmaps)
+    }
+}
+
+/// Reads a root NBT tag from a file, decompressing it if it's gzipped.
+fn read_nbt_file(path: &Path) -> Result<Nbt, WorldError> {
+    let bytes = std::fs::read(path)?;
+
+    if bytes.starts_with(&[0x1f, 0x8b]) {
+        let mut decompressed = Vec::new();
+        GzDecoder::new(&bytes[..]).read_to_end(&mut decompressed)?;
+        Ok(Nbt::read_from(&mut &decompressed[..])?)
+    } else {
+        Ok(Nbt::read_from(&mut &bytes[..])?)
+    }
+}
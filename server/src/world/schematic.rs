@@ -0,0 +1,139 @@
+use std::{collections::HashMap, path::Path};
+
+use log::{debug, warn};
+use protocol::{nbt::Nbt, PacketField, VarInt};
+
+use super::{
+    blocks::{self, BlockRegistry},
+    World, WorldError,
+};
+
+/// Pastes a Sponge schematic (version 2 or 3) into the world, placing the schematic origin at
+/// `origin`, the same way WorldEdit would paste it.
+pub fn load(
+    path: &Path,
+    blocks: &BlockRegistry,
+    origin: [i32; 3],
+    world: &mut World,
+) -> Result<(), WorldError> {
+    let root = super::read_nbt_file(path)?;
+
+    // Version 3 wraps everything in an extra compound, version 2 doesn't.
+    let schematic = root.get("Schematic").unwrap_or(&root);
+    let version = schematic
+        .get("Version")
+        .and_then(Nbt::as_i64)
+        .ok_or(WorldError::InvalidSchematic("missing version"))?;
+
+    let container = match version {
+        2 => schematic,
+        3 => schematic
+            .get("Blocks")
+            .ok_or(WorldError::InvalidSchematic("missing blocks"))?,
+        _ => return Err(WorldError::InvalidSchematic("unsupported version")),
+    };
+
+    let dimension = |key| {
+        schematic
+            .get(key)
+            .and_then(Nbt::as_i64)
+            .map(|v| v as u16 as i32)
+            .ok_or(WorldError::InvalidSchematic("missing dimensions"))
+    };
+    let (width, height, length) = (
+        dimension("Width")?,
+        dimension("Height")?,
+        dimension("Length")?,
+    );
+
+    let offset = schematic
+        .get("Offset")
+        .and_then(Nbt::as_int_array)
+        .filter(|offset| offset.len() == 3)
+        .unwrap_or(&[0, 0, 0]);
+    let base = [
+        origin[0] + offset[0],
+        origin[1] + offset[1],
+        origin[2] + offset[2],
+    ];
+
+    let palette = container
+        .get("Palette")
+        .and_then(Nbt::as_compound)
+        .ok_or(WorldError::InvalidSchematic("missing palette"))?;
+    let palette = palette
+        .iter()
+        .filter_map(|(state, index)| {
+            let id = blocks.parse(state).unwrap_or_else(|| {
+                warn!(
+                    "unknown block state {} in schematic, replacing with air",
+                    state
+                );
+                0
+            });
+            index.as_i64().map(|index| (index as i32, id))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let data = container
+        .get(if version == 2 { "BlockData" } else { "Data" })
+        .and_then(Nbt::as_bytes)
+        .ok_or(WorldError::InvalidSchematic("missing block data"))?;
+
+    let mut data = data;
+    for i in 0..width * height * length {
+        let index = VarInt::read_from(&mut data)?.0;
+        let state = palette.get(&index).copied().unwrap_or(0);
+        if state == 0 {
+            continue;
+        }
+
+        let (x, y, z) = (i % width, i / (width * length), (i / width) % length);
+        world.set_block(base[0] + x, base[1] + y, base[2] + z, state);
+    }
+
+    let block_entities = container
+        .get("BlockEntities")
+        .or_else(|| container.get("TileEntities"))
+        .and_then(Nbt::as_list)
+        .unwrap_or(&[]);
+
+    for block_entity in block_entities {
+        let (id, position) = match (
+            block_entity.get("Id").and_then(Nbt::as_str),
+            block_entity.get("Pos").and_then(Nbt::as_int_array),
+        ) {
+            (Some(id), Some(position)) if position.len() == 3 => (id, position),
+            _ => {
+                debug!("skipping block entity without id or position");
+                continue;
+            }
+        };
+
+        let kind = match blocks::block_entity_kind(id) {
+            Some(kind) => kind,
+            None => {
+                warn!("unknown block entity {} in schematic", id);
+                continue;
+            }
+        };
+
+        // Version 3 nests the block entity data, version 2 stores it next to the ID and position.
+        let mut data = match block_entity.get("Data").and_then(Nbt::as_compound) {
+            Some(data) => data.clone(),
+            None => block_entity.as_compound().cloned().unwrap_or_default(),
+        };
+        data.remove("Id");
+        data.remove("Pos");
+
+        world.add_block_entity(
+            base[0] + position[0],
+            base[1] + position[1],
+            base[2] + position[2],
+            kind,
+            data,
+        );
+    }
+
+    Ok(())
+}